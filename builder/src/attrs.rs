@@ -0,0 +1,685 @@
+use crate::{FieldInfo, Pattern, SetterOptions, StructInfo, SubBuilderOptions};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use std::collections::HashSet;
+use syn::{
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+    token, Attribute, Error, Expr, ExprLit, ExprPath, Lit, LitStr, Path, Token, Type, Visibility,
+};
+
+/// A single option inside `#[builder(...)]`, such as `each = "arg"`,
+/// `default` or `setter(into)`.
+pub(crate) struct BuilderOption {
+    name: Path,
+    value: OptionValue,
+}
+
+enum OptionValue {
+    /// `name`
+    Flag,
+    /// `name = expr`
+    Assign(Token![=], Box<Expr>),
+    /// `name(option, ...)`
+    List(token::Paren, Punctuated<BuilderOption, Token![,]>),
+    /// `name(...)` with something other than options inside, along with the
+    /// error from parsing them as options.
+    Tokens(token::Paren, TokenStream2, Error),
+}
+
+impl Parse for BuilderOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input
+            .call(Path::parse_mod_style)
+            .map_err(|err| Error::new(err.span(), "expected a builder option"))?;
+        let value = if input.peek(Token![=]) {
+            OptionValue::Assign(input.parse()?, Box::new(input.parse()?))
+        } else if input.peek(token::Paren) {
+            let content;
+            let paren = syn::parenthesized!(content in input);
+            // Whether options were expected depends on the option.
+            let tokens = content.parse::<TokenStream2>()?;
+            match Punctuated::<BuilderOption, Token![,]>::parse_terminated.parse2(tokens.clone()) {
+                Ok(options) => OptionValue::List(paren, options),
+                Err(err) => OptionValue::Tokens(paren, tokens, err),
+            }
+        } else {
+            OptionValue::Flag
+        };
+
+        Ok(BuilderOption { name, value })
+    }
+}
+
+impl ToTokens for BuilderOption {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.name.to_tokens(tokens);
+        match &self.value {
+            OptionValue::Flag => {}
+            OptionValue::Assign(eq, expr) => {
+                eq.to_tokens(tokens);
+                expr.to_tokens(tokens);
+            }
+            OptionValue::List(paren, options) => {
+                paren.surround(tokens, |tokens| options.to_tokens(tokens));
+            }
+            OptionValue::Tokens(paren, inner, _) => {
+                paren.surround(tokens, |tokens| inner.to_tokens(tokens));
+            }
+        }
+    }
+}
+
+impl BuilderOption {
+    fn name(&self) -> String {
+        self.name
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// `name`
+    fn flag(&self) -> syn::Result<()> {
+        match &self.value {
+            OptionValue::Flag => Ok(()),
+            _ => Err(Error::new_spanned(
+                self,
+                format!("expected `{}` without a value", self.name()),
+            )),
+        }
+    }
+
+    /// `name` or `name = true|false`
+    fn bool_flag(&self) -> syn::Result<bool> {
+        match &self.value {
+            OptionValue::Flag => Ok(true),
+            OptionValue::Assign(_, expr) => match &**expr {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Bool(lit_bool),
+                    ..
+                }) => Ok(lit_bool.value),
+                expr => Err(Error::new_spanned(expr, "expected `true` or `false`")),
+            },
+            OptionValue::List(..) | OptionValue::Tokens(..) => Err(Error::new_spanned(
+                self,
+                format!("expected `{0}` or `{0} = true|false`", self.name()),
+            )),
+        }
+    }
+
+    /// `name = expr`
+    fn expr(&self) -> syn::Result<&Expr> {
+        match &self.value {
+            OptionValue::Assign(_, expr) => Ok(expr),
+            _ => Err(Error::new_spanned(
+                self,
+                format!("expected `{} = ...`", self.name()),
+            )),
+        }
+    }
+
+    /// `name = "..."`
+    fn lit_str(&self) -> syn::Result<&LitStr> {
+        match self.expr()? {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit_str),
+                ..
+            }) => Ok(lit_str),
+            expr => Err(Error::new_spanned(expr, "expected a string literal")),
+        }
+    }
+
+    /// `name = "ident"`
+    fn ident(&self) -> syn::Result<Ident> {
+        let lit_str = self.lit_str()?;
+        lit_str
+            .parse()
+            .map_err(|_| Error::new_spanned(lit_str, "expected an identifier"))
+    }
+
+    /// `name = "visibility"`
+    fn vis(&self) -> syn::Result<Visibility> {
+        let lit_str = self.lit_str()?;
+        lit_str
+            .parse()
+            .map_err(|_| Error::new_spanned(lit_str, "expected a visibility"))
+    }
+
+    /// `name = "type"`
+    fn ty(&self) -> syn::Result<Type> {
+        let lit_str = self.lit_str()?;
+        lit_str
+            .parse()
+            .map_err(|_| Error::new_spanned(lit_str, "expected a type"))
+    }
+
+    /// `name = path` or `name = "path"`
+    fn path(&self) -> syn::Result<Path> {
+        match self.expr()? {
+            Expr::Path(ExprPath { path, .. }) => Ok(path.clone()),
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit_str),
+                ..
+            }) => lit_str.parse(),
+            expr => Err(Error::new_spanned(expr, "expected a path to a function")),
+        }
+    }
+
+    /// `name(option, ...)`
+    fn list(&self) -> syn::Result<&Punctuated<BuilderOption, Token![,]>> {
+        match &self.value {
+            OptionValue::List(_, options) => Ok(options),
+            OptionValue::Tokens(_, _, err) => Err(err.clone()),
+            _ => Err(Error::new_spanned(
+                self,
+                format!("expected `{}(...)`", self.name()),
+            )),
+        }
+    }
+}
+
+/// Collects every error found while parsing, so they can all be reported at
+/// once.
+#[derive(Default)]
+pub(crate) struct Errors(Option<Error>);
+
+impl Errors {
+    pub(crate) fn push(&mut self, err: Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    pub(crate) fn finish(self) -> syn::Result<()> {
+        match self.0 {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Rejects an option that was already given for the same item.
+fn check_unique(seen: &mut HashSet<String>, option: &BuilderOption) -> syn::Result<()> {
+    if seen.insert(option.name()) {
+        Ok(())
+    } else {
+        Err(Error::new_spanned(
+            option,
+            format!("duplicate `{}` option", option.name()),
+        ))
+    }
+}
+
+/// Parses the options of every `#[builder(...)]` attribute, calling `apply`
+/// with each one.
+fn for_each_option(
+    attrs: &[Attribute],
+    mut apply: impl FnMut(&BuilderOption) -> syn::Result<()>,
+) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let mut seen = HashSet::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("builder")) {
+        let options =
+            match attr.parse_args_with(Punctuated::<BuilderOption, Token![,]>::parse_terminated) {
+                Ok(options) => options,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+
+        for option in options.iter() {
+            if let Err(err) = check_unique(&mut seen, option).and_then(|()| apply(option)) {
+                errors.push(err);
+            }
+        }
+    }
+
+    errors.finish()
+}
+
+/// Parses the options given on an enum, which apply to the builders of all of
+/// its variants. The builders are named after their variants, so naming them
+/// is left to the variants.
+pub(crate) fn parse_enum_options(attrs: &[Attribute]) -> syn::Result<Vec<(String, TokenStream2)>> {
+    let mut options = Vec::new();
+    for_each_option(attrs, |option| match option.name().as_str() {
+        name @ ("name" | "constructor") => Err(Error::new_spanned(
+            option,
+            format!("`{}` can only be given on a variant of an enum", name),
+        )),
+        name => {
+            options.push((name.to_owned(), option.to_token_stream()));
+            Ok(())
+        }
+    })?;
+    Ok(options)
+}
+
+/// The builder attributes of an enum variant with the options of the enum
+/// added, except for those the variant overrides.
+pub(crate) fn variant_attrs(
+    enum_options: &[(String, TokenStream2)],
+    attrs: &[Attribute],
+) -> Vec<Attribute> {
+    // Malformed options are reported when the variant's options are parsed.
+    let mut overridden = HashSet::new();
+    let _ = for_each_option(attrs, |option| {
+        overridden.insert(option.name());
+        Ok(())
+    });
+
+    enum_options
+        .iter()
+        .filter(|(name, _)| !overridden.contains(name))
+        .map(|(_, option)| -> Attribute { syn::parse_quote!(#[builder(#option)]) })
+        .chain(attrs.iter().cloned())
+        .collect()
+}
+
+/// Parses the arguments of `setter(...)`.
+fn parse_setter_options(option: &BuilderOption, setter: &mut SetterOptions) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let mut seen = HashSet::new();
+
+    for arg in option.list()? {
+        let result = check_unique(&mut seen, arg).and_then(|()| match arg.name().as_str() {
+            // `setter(into)`
+            "into" => arg.bool_flag().map(|into| setter.into = into),
+            // `setter(custom)`
+            "custom" => arg.bool_flag().map(|custom| setter.custom = custom),
+            // `setter(strip_option)`
+            "strip_option" => arg
+                .bool_flag()
+                .map(|strip_option| setter.strip_option = strip_option),
+            _ => Err(Error::new_spanned(
+                &arg.name,
+                "unknown setter option, expected `into`, `strip_option` or `custom`",
+            )),
+        });
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    errors.finish()
+}
+
+/// Parses the arguments of `each(...)`.
+fn parse_each_options(option: &BuilderOption, info: &mut FieldInfo) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let mut seen = HashSet::new();
+
+    for arg in option.list()? {
+        let result = check_unique(&mut seen, arg).and_then(|()| match arg.name().as_str() {
+            // `each(name = "...")`
+            "name" => arg.ident().map(|name| info.builder_attr_ident = Some(name)),
+            // `each(append)`
+            "append" => arg.bool_flag().map(|append| info.append = append),
+            _ => Err(Error::new_spanned(
+                &arg.name,
+                "unknown each option, expected `name` or `append`",
+            )),
+        });
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    if !seen.contains("name") {
+        errors.push(Error::new_spanned(
+            option,
+            "expected `each(name = \"...\")`",
+        ));
+    }
+
+    errors.finish()
+}
+
+/// Parses the arguments of `sub_builder(...)`.
+fn parse_sub_builder_options(
+    option: &BuilderOption,
+    sub_builder: &mut SubBuilderOptions,
+) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let mut seen = HashSet::new();
+
+    for arg in option.list()? {
+        let result = check_unique(&mut seen, arg).and_then(|()| match arg.name().as_str() {
+            // `sub_builder(builder = "...")`
+            "builder" => arg
+                .path()
+                .map(|builder| sub_builder.builder = Some(builder)),
+            // `sub_builder(build_fn = "...")`
+            "build_fn" => arg
+                .ident()
+                .map(|build_fn| sub_builder.build_fn = Some(build_fn)),
+            _ => Err(Error::new_spanned(
+                &arg.name,
+                "unknown sub_builder option, expected `builder` or `build_fn`",
+            )),
+        });
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    errors.finish()
+}
+
+/// Parses the arguments of `build_fn(...)`.
+fn parse_build_fn_options(option: &BuilderOption, info: &mut StructInfo) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let mut seen = HashSet::new();
+
+    for arg in option.list()? {
+        let result = check_unique(&mut seen, arg).and_then(|()| match arg.name().as_str() {
+            // `build_fn(name = "...")`
+            "name" => arg.ident().map(|name| info.build_fn = Some(name)),
+            // `build_fn(error = "...")`
+            "error" => arg.ty().map(|error| info.build_error = Some(error)),
+            _ => Err(Error::new_spanned(
+                &arg.name,
+                "unknown build_fn option, expected `name` or `error`",
+            )),
+        });
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    errors.finish()
+}
+
+/// Parses the arguments of `derive(...)`, which must all be trait paths.
+fn parse_derives(option: &BuilderOption, derives: &mut Vec<Path>) -> syn::Result<()> {
+    let mut errors = Errors::default();
+
+    for arg in option.list()? {
+        match arg.value {
+            OptionValue::Flag => derives.push(arg.name.clone()),
+            _ => errors.push(Error::new_spanned(arg, "expected a trait to derive")),
+        }
+    }
+
+    errors.finish()
+}
+
+pub(crate) fn parse_struct_attrs(attrs: &[Attribute]) -> syn::Result<StructInfo> {
+    let mut info = StructInfo::default();
+    let mut pattern_span = Span::call_site();
+    let mut deserialize_option = None;
+    let mut merge_option = None;
+
+    for_each_option(attrs, |option| {
+        match option.name().as_str() {
+            // `#[builder(typestate)]`
+            "typestate" => {
+                option.flag()?;
+                info.typestate = true;
+            }
+            // `#[builder(doc = "...")]`
+            "doc" => info.doc = Some(option.lit_str()?.clone()),
+            // `#[builder(deserialize)]`
+            "deserialize" => {
+                option.flag()?;
+                if !cfg!(feature = "serde") {
+                    return Err(Error::new_spanned(
+                        option,
+                        "`deserialize` requires the `serde` feature of derive_builder",
+                    ));
+                }
+                deserialize_option = Some(option.name.clone());
+                info.deserialize = true;
+                // Deserialized partial values are combined with `merge`.
+                info.merge = true;
+            }
+            // `#[builder(merge)]`
+            "merge" => {
+                option.flag()?;
+                merge_option = Some(option.name.clone());
+                info.merge = true;
+            }
+            // `#[builder(no_std)]`
+            "no_std" => {
+                option.flag()?;
+                info.no_std = true;
+            }
+            // `#[builder(setter(...))]`
+            "setter" => parse_setter_options(option, &mut info.setter)?,
+            // `#[builder(try_setter)]`
+            "try_setter" => info.setter.try_setter = option.bool_flag()?,
+            // `#[builder(validate = "...")]`
+            "validate" => info.validate = Some(option.path()?),
+            // `#[builder(vis = "...")]`
+            "vis" => info.vis = Some(option.vis()?),
+            // `#[builder(name = "...")]`
+            "name" => info.name = Some(option.ident()?),
+            // `#[builder(constructor = "...")]`
+            "constructor" => info.constructor = Some(option.ident()?),
+            // `#[builder(build_fn(...))]`
+            "build_fn" => parse_build_fn_options(option, &mut info)?,
+            // `#[builder(derive(...))]`
+            "derive" => parse_derives(option, &mut info.derives)?,
+            // `#[builder(attr(...))]`
+            "attr" => info
+                .attrs
+                .extend(option.list()?.iter().map(|attr| quote!(#[#attr]))),
+            // `#[builder(pattern = "...")]`
+            "pattern" => {
+                let lit_str = option.lit_str()?;
+                pattern_span = lit_str.span();
+                info.pattern = Some(match lit_str.value().as_str() {
+                    "mutable" => Pattern::Mutable,
+                    "owned" => Pattern::Owned,
+                    "immutable" => Pattern::Immutable,
+                    _ => {
+                        return Err(Error::new_spanned(
+                            lit_str,
+                            "expected \"owned\", \"mutable\" or \"immutable\"",
+                        ))
+                    }
+                });
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    &option.name,
+                    format!("unknown builder option `{}`", option.name()),
+                ))
+            }
+        }
+
+        Ok(())
+    })?;
+
+    // Typestate setters have to change the type of the builder.
+    if info.typestate {
+        match info.pattern {
+            None | Some(Pattern::Owned) => info.pattern = Some(Pattern::Owned),
+            Some(_) => {
+                return Err(Error::new(
+                    pattern_span,
+                    "typestate builders always use the owned pattern",
+                ))
+            }
+        }
+
+        // The state of a deserialized builder is not known in advance.
+        if let Some(deserialize) = deserialize_option {
+            return Err(Error::new_spanned(
+                deserialize,
+                "typestate builders cannot be deserialized",
+            ));
+        }
+
+        // Fields set in only one of the builders are in different states.
+        if let Some(merge) = merge_option {
+            return Err(Error::new_spanned(
+                merge,
+                "typestate builders cannot be merged",
+            ));
+        }
+    }
+
+    Ok(info)
+}
+
+pub(crate) fn parse_field_attrs(attrs: &[Attribute], info: &mut FieldInfo) -> syn::Result<()> {
+    let mut vis_option = None;
+    let mut sub_builder = None;
+    let mut skip = None;
+    let mut each_option = None;
+    let mut try_setter_option = None;
+
+    for_each_option(attrs, |option| {
+        match option.name().as_str() {
+            // `#[builder(vis = "...")]` or `#[builder(private)]`
+            "vis" | "private" => {
+                if let Some(other) = vis_option.replace(option.name()) {
+                    return Err(Error::new_spanned(
+                        option,
+                        format!("`{}` conflicts with `{}`", option.name(), other),
+                    ));
+                }
+
+                info.vis = if option.name() == "vis" {
+                    option.vis()?
+                } else {
+                    option.flag()?;
+                    Visibility::Inherited
+                };
+            }
+            // `#[builder(name = "...")]`
+            "name" => info.ident = option.ident()?,
+            // `#[builder(each = "...")]` or `#[builder(each(...))]`
+            "each" => {
+                match &option.value {
+                    OptionValue::List(..) | OptionValue::Tokens(..) => {
+                        parse_each_options(option, info)?
+                    }
+                    _ => info.builder_attr_ident = Some(option.ident()?),
+                }
+                each_option = Some(option.to_token_stream());
+            }
+            // `#[builder(default)]` or `#[builder(default = ...)]`
+            "default" => {
+                info.default = Some(match &option.value {
+                    OptionValue::Flag => quote!(core::default::Default::default()),
+                    OptionValue::Assign(_, expr) => quote!(#expr),
+                    OptionValue::List(..) | OptionValue::Tokens(..) => {
+                        return Err(Error::new_spanned(
+                            option,
+                            "expected `default` or `default = ...`",
+                        ))
+                    }
+                });
+            }
+            // `#[builder(setter(...))]`
+            "setter" => parse_setter_options(option, &mut info.setter)?,
+            // `#[builder(try_setter)]`
+            "try_setter" => {
+                info.setter.try_setter = option.bool_flag()?;
+                try_setter_option = info.setter.try_setter.then(|| option.name.clone());
+            }
+            // `#[builder(validate_with = ...)]`
+            "validate_with" => info.validate_with = Some(option.path()?),
+            // `#[builder(doc = "...")]`
+            "doc" => {
+                let doc = option.lit_str()?;
+                info.doc = quote!(#[doc = #doc]);
+            }
+            // `#[builder(optional)]` or `#[builder(optional = false)]`
+            "optional" => info.optional = Some(option.bool_flag()?),
+            // `#[builder(skip)]`
+            "skip" => {
+                option.flag()?;
+                skip = Some(option.name.clone());
+            }
+            // `#[builder(sub_builder)]` or `#[builder(sub_builder(...))]`
+            "sub_builder" => {
+                match &option.value {
+                    OptionValue::List(..) | OptionValue::Tokens(..) => {
+                        parse_sub_builder_options(option, &mut info.sub_builder_options)?
+                    }
+                    _ => option.flag()?,
+                }
+                sub_builder = Some(option.name.clone());
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    &option.name,
+                    format!("unknown builder option `{}`", option.name()),
+                ))
+            }
+        }
+
+        Ok(())
+    })?;
+
+    // An `each` setter named like the field replaces the whole-collection
+    // setter, which `append` and `try_setter` apply to.
+    if info.builder_attr_ident.as_ref() == Some(&info.ident) {
+        let conflict = if info.append {
+            each_option.map(|each| (each, "append"))
+        } else {
+            try_setter_option.map(|try_setter| (try_setter.to_token_stream(), "try_setter"))
+        };
+        if let Some((tokens, option)) = conflict {
+            return Err(Error::new_spanned(
+                tokens,
+                format!(
+                    "`{}` requires the `each` setter to be named differently from the field",
+                    option
+                ),
+            ));
+        }
+    }
+
+    // The slot of a `sub_builder` field holds a builder rather than a value.
+    if let Some(sub_builder) = sub_builder {
+        let conflict = if info.builder_attr_ident.is_some() {
+            Some("each")
+        } else if info.default.is_some() {
+            Some("default")
+        } else if info.validate_with.is_some() {
+            Some("validate_with")
+        } else {
+            None
+        };
+        if let Some(conflict) = conflict {
+            return Err(Error::new_spanned(
+                sub_builder,
+                format!("`sub_builder` conflicts with `{}`", conflict),
+            ));
+        }
+        info.sub_builder = true;
+    }
+
+    // A skipped field has no slot and is always set to its default.
+    if let Some(skip) = skip {
+        let conflict = if info.builder_attr_ident.is_some() {
+            Some("each")
+        } else if info.sub_builder {
+            Some("sub_builder")
+        } else if info.validate_with.is_some() {
+            Some("validate_with")
+        } else {
+            None
+        };
+        if let Some(conflict) = conflict {
+            return Err(Error::new_spanned(
+                skip,
+                format!("`skip` conflicts with `{}`", conflict),
+            ));
+        }
+        info.skip = true;
+        info.default
+            .get_or_insert_with(|| quote!(core::default::Default::default()));
+    }
+
+    Ok(())
+}
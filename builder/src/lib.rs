@@ -1,60 +1,596 @@
+mod attrs;
+
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
-    spanned::Spanned, AngleBracketedGenericArguments, Error, Expr, ExprAssign, ExprLit, ExprPath,
-    FieldsNamed, GenericArgument, ItemStruct, Lit, PathArguments, Type, TypePath,
+    AngleBracketedGenericArguments, Attribute, ConstParam, Data, DataEnum, DataStruct, DeriveInput,
+    Error, Fields, GenericArgument, GenericParam, Generics, LifetimeDef, LitStr, Member, Path,
+    PathArguments, PathSegment, Type, TypeParam, TypePath, Visibility,
 };
 
 struct FieldInfo<'a> {
-    ident: &'a Ident,
+    /// The name of the builder slot and its setter.
+    ident: Ident,
+    /// How the field is named when constructing the target.
+    member: Member,
     ty: &'a Type,
     is_optional: bool,
+    /// Set by `#[builder(optional)]` to override whether the field's type is
+    /// taken to be an `Option`.
+    optional: Option<bool>,
     builder_attr_ident: Option<Ident>,
+    default: Option<TokenStream2>,
+    setter: SetterOptions,
+    validate_with: Option<Path>,
+    /// The visibility of the field's setters.
+    vis: Visibility,
+    /// The whole-collection setter of an `each` field appends to it.
+    append: bool,
+    /// The field is set through a nested builder of its type.
+    sub_builder: bool,
+    sub_builder_options: SubBuilderOptions,
+    /// The `#[doc]` attributes copied onto the field's setters.
+    doc: TokenStream2,
+    /// The field has no slot and is always set to its default.
+    skip: bool,
 }
 
-fn get_inner_type<'a>(ty: &'a Type, outer_ident: &str) -> Option<&'a Type> {
-    if let Type::Path(TypePath { path, .. }) = ty {
-        if let Some(segment) = path.segments.first() {
-            if segment.ident == outer_ident {
-                if let PathArguments::AngleBracketed(AngleBracketedGenericArguments {
-                    args, ..
-                }) = &segment.arguments
-                {
-                    if let Some(GenericArgument::Type(inner_ty)) = args.first() {
-                        return Some(inner_ty);
-                    }
-                }
+impl FieldInfo<'_> {
+    /// Whether `build()` must fail if this field was never set.
+    fn is_required(&self) -> bool {
+        !self.is_optional
+            && self.builder_attr_ident.is_none()
+            && self.default.is_none()
+            && !self.sub_builder
+    }
+
+    /// The builder and error types of a `sub_builder` field.
+    fn sub_builder_types(&self) -> Option<(Path, Path)> {
+        if self.sub_builder {
+            sub_builder_types(self.ty, self.sub_builder_options.builder.as_ref())
+        } else {
+            None
+        }
+    }
+
+    /// Whether the field's slot tells an explicit `None` apart from the field
+    /// not being set, so that `None` can override a default or be merged.
+    fn has_explicit_none(&self) -> bool {
+        self.is_optional && !self.sub_builder && self.builder_attr_ident.is_none()
+    }
+
+    /// How errors name the field: as in the target, or by its setter for a
+    /// field of a tuple struct.
+    fn name(&self) -> String {
+        match &self.member {
+            Member::Named(member) => member.to_string(),
+            Member::Unnamed(_) => self.ident.to_string(),
+        }
+    }
+
+    /// The contents of the field's builder slot holding `value`, a value of
+    /// the field.
+    fn slot_from_value(&self, value: TokenStream2) -> TokenStream2 {
+        if self.sub_builder && self.is_optional {
+            quote!(core::option::Option::map(#value, core::convert::From::from))
+        } else if self.sub_builder {
+            quote!(core::option::Option::Some(core::convert::From::from(#value)))
+        } else if self.has_explicit_none() || !self.is_optional {
+            quote!(core::option::Option::Some(#value))
+        } else {
+            value
+        }
+    }
+
+    /// The type held by the field's builder slot.
+    fn slot_ty(&self) -> TokenStream2 {
+        let ty = self.ty;
+        match self.sub_builder_types() {
+            Some((builder, _)) => quote!(#builder),
+            None if self.has_explicit_none() => quote!(core::option::Option<#ty>),
+            None => quote!(#ty),
+        }
+    }
+}
+
+/// Overrides for the nested builder of a `sub_builder` field, for types whose
+/// builder or build method was renamed.
+#[derive(Default)]
+struct SubBuilderOptions {
+    /// The nested builder, `{Ty}Builder` unless given.
+    builder: Option<Path>,
+    /// The nested build method, `build` unless given.
+    build_fn: Option<Ident>,
+}
+
+#[derive(Clone, Copy)]
+struct SetterOptions {
+    /// Setters accept `impl Into<T>`.
+    into: bool,
+    /// Setters of `Option<T>` fields accept `T` instead of `Option<T>`.
+    strip_option: bool,
+    /// A `try_` setter accepting any `TryInto<T>` is generated as well.
+    try_setter: bool,
+    /// No setters are generated, leaving them to be written by hand.
+    custom: bool,
+}
+
+impl Default for SetterOptions {
+    fn default() -> Self {
+        SetterOptions {
+            into: false,
+            strip_option: true,
+            try_setter: false,
+            custom: false,
+        }
+    }
+}
+
+/// How the builder is passed to its setters and to `build()`.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum Pattern {
+    /// Setters and `build()` take `&mut self`; `build()` moves the values out.
+    #[default]
+    Mutable,
+    /// Setters and `build()` take `self` by value.
+    Owned,
+    /// Setters and `build()` take `&self`, working on a clone of the builder.
+    Immutable,
+}
+
+impl Pattern {
+    /// The receiver and return type of a setter, and the statement binding
+    /// `builder` to the builder that is modified and returned.
+    fn setter_parts(self) -> (TokenStream2, TokenStream2, TokenStream2) {
+        match self {
+            Pattern::Mutable => (
+                quote!(&mut self),
+                quote!(&mut Self),
+                quote!(let builder = self;),
+            ),
+            Pattern::Owned => (quote!(self), quote!(Self), quote!(let mut builder = self;)),
+            Pattern::Immutable => (
+                quote!(&self),
+                quote!(Self),
+                quote!(let mut builder = core::clone::Clone::clone(self);),
+            ),
+        }
+    }
+
+    /// The attribute warning about a setter whose returned builder is unused,
+    /// for patterns where the setter does not modify the builder in place.
+    fn must_use(self) -> TokenStream2 {
+        match self {
+            Pattern::Mutable => quote!(),
+            Pattern::Owned | Pattern::Immutable => quote! {
+                #[must_use = "the builder is returned with the change, not changed in place"]
+            },
+        }
+    }
+
+    /// The receiver of `build()`, the statement binding `builder` to the
+    /// builder whose values are used and any bounds needed for it.
+    fn build_parts(self) -> (TokenStream2, TokenStream2, TokenStream2) {
+        match self {
+            Pattern::Mutable => (quote!(&mut self), quote!(let builder = self;), quote!()),
+            Pattern::Owned => (quote!(self), quote!(let builder = self;), quote!()),
+            Pattern::Immutable => (
+                quote!(&self),
+                quote!(let builder = core::clone::Clone::clone(self);),
+                quote!(where Self: core::clone::Clone),
+            ),
+        }
+    }
+
+    /// Moves a value out of a builder slot.
+    fn take_value(self, ident: &Ident) -> TokenStream2 {
+        match self {
+            Pattern::Mutable => quote!(builder.#ident.take()),
+            Pattern::Owned | Pattern::Immutable => quote!(builder.#ident),
+        }
+    }
+}
+
+#[derive(Default)]
+struct StructInfo {
+    typestate: bool,
+    pattern: Option<Pattern>,
+    setter: SetterOptions,
+    validate: Option<Path>,
+    vis: Option<Visibility>,
+    name: Option<Ident>,
+    constructor: Option<Ident>,
+    build_fn: Option<Ident>,
+    build_error: Option<Type>,
+    /// Traits derived for the builder.
+    derives: Vec<Path>,
+    /// Attributes forwarded onto the builder.
+    attrs: Vec<TokenStream2>,
+    /// The generated code only uses `core` and `alloc`.
+    no_std: bool,
+    /// Replaces the documentation of the builder.
+    doc: Option<LitStr>,
+    /// The builder implements `serde::Deserialize`.
+    deserialize: bool,
+    /// The builder has a `merge` method.
+    merge: bool,
+}
+
+/// The type a builder is generated for.
+#[derive(Clone)]
+struct Target<'a> {
+    /// The struct or enum that gets the builder constructor.
+    ident: &'a Ident,
+    vis: &'a Visibility,
+    generics: &'a Generics,
+    /// How `build()` names the value it constructs, e.g. `Message::Request`.
+    path: TokenStream2,
+    /// The name of the builder constructor, e.g. `request_builder`.
+    constructor: Ident,
+    builder_ident: Ident,
+    /// The name of the build method.
+    build_fn: Ident,
+    /// A custom error type returned by the build method, which must implement
+    /// `From` for the generated error type.
+    build_error: Option<Type>,
+    /// Whether the builder needs a `PhantomData` of the target, because its
+    /// fields may not use all of the target's generics, e.g. those of an enum
+    /// variant or with skipped fields.
+    marker: bool,
+    /// Whether the target is an enum variant, so that an existing value of
+    /// the enum may be a different variant.
+    variant: bool,
+}
+
+impl Target<'_> {
+    /// The generated error type, named after the builder.
+    fn error_ident(&self) -> Ident {
+        quote::format_ident!("{}Error", self.builder_ident)
+    }
+
+    /// The target as named in docs, e.g. `Message::Request`.
+    fn doc_path(&self) -> String {
+        self.path.to_string().replace(' ', "")
+    }
+
+    /// The docs of the builder constructor and of the build method.
+    fn fn_docs(&self) -> (String, String) {
+        (
+            format!("Creates a [`{}`] with no field set.", self.builder_ident),
+            format!(
+                "Builds the [`{}`], failing if a required field was not set.",
+                self.doc_path()
+            ),
+        )
+    }
+
+    /// The error type returned by the build method.
+    fn build_error(&self) -> TokenStream2 {
+        match &self.build_error {
+            Some(build_error) => quote!(#build_error),
+            None => {
+                let error_ident = self.error_ident();
+                quote!(#error_ident)
             }
         }
     }
+}
+
+/// The last segment of a path type that may name a type of the standard
+/// library, either as imported, e.g. `Option` or `HashMap` after a `use`, or
+/// by a path into `std`, `core` or `alloc`, e.g. `::core::option::Option`.
+fn std_type_segment(ty: &Type) -> Option<&PathSegment> {
+    if let Type::Path(TypePath { qself: None, path }) = ty {
+        let first = path.segments.first()?;
+        let is_std = if path.segments.len() == 1 {
+            path.leading_colon.is_none()
+        } else {
+            first.ident == "std" || first.ident == "core" || first.ident == "alloc"
+        };
+        if is_std {
+            return path.segments.last();
+        }
+    }
 
     None
 }
 
-fn field_setters(info: &FieldInfo) -> TokenStream2 {
-    let ident = info.ident;
+/// The type arguments of a path segment, e.g. `K` and `V` in `HashMap<K, V>`.
+fn type_arguments(segment: &PathSegment) -> Vec<&Type> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The `T` of a field of type `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    std_type_segment(ty)
+        .filter(|segment| segment.ident == "Option")
+        .and_then(|segment| type_arguments(segment).first().copied())
+}
+
+/// The first type argument of a field marked with `#[builder(optional)]`,
+/// whose type is taken to be an alias of `Option`.
+fn first_type_argument(ty: &Type) -> Option<&Type> {
+    if let Type::Path(TypePath { qself: None, path }) = ty {
+        let segment = path.segments.last()?;
+        return type_arguments(segment).first().copied();
+    }
+
+    None
+}
+
+/// The builder and error types that `#[derive(Builder)]` generates for a
+/// struct type, e.g. `InnerBuilder<T>` and `InnerBuilderError` for `Inner<T>`,
+/// or for the builder given with `sub_builder(builder = "...")`.
+///
+/// The names keep the span of the field's type, so that errors about a type
+/// without such a builder point at the field.
+fn sub_builder_types(ty: &Type, builder: Option<&Path>) -> Option<(Path, Path)> {
+    let builder = match (builder, ty) {
+        (Some(builder), _) => builder.clone(),
+        (None, Type::Path(TypePath { qself: None, path })) => {
+            let mut builder = path.clone();
+            let segment = builder.segments.last_mut()?;
+            segment.ident = Ident::new(&format!("{}Builder", segment.ident), segment.ident.span());
+            builder
+        }
+        (None, _) => return None,
+    };
+
+    let mut error = builder.clone();
+    let segment = error.segments.last_mut()?;
+    segment.ident = Ident::new(&format!("{}Error", segment.ident), segment.ident.span());
+    segment.arguments = PathArguments::None;
+
+    Some((builder, error))
+}
+
+/// The kind of items a collection used with `each` is extended with.
+enum CollectionItem<'a> {
+    /// Sequences and sets, extended one element at a time.
+    Single(&'a Type),
+    /// Maps, extended one key-value pair at a time.
+    Pair(&'a Type, &'a Type),
+    /// Any other collection; the item type is left to `Extend`.
+    Unknown,
+}
+
+fn collection_item(ty: &Type) -> CollectionItem<'_> {
+    if let Some(segment) = std_type_segment(ty) {
+        let ident = segment.ident.to_string();
+        match (ident.as_str(), type_arguments(segment).as_slice()) {
+            (
+                "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "HashSet" | "BTreeSet",
+                [item, ..],
+            ) => return CollectionItem::Single(item),
+            ("HashMap" | "BTreeMap", [key, value, ..]) => return CollectionItem::Pair(key, value),
+            _ => {}
+        }
+    }
+
+    CollectionItem::Unknown
+}
+
+/// The type of value a field's setter takes, before any `Into` conversion.
+fn setter_ty(info: &FieldInfo) -> TokenStream2 {
+    let ty = info.ty;
+    if info.is_optional && !info.setter.strip_option {
+        quote!(core::option::Option<#ty>)
+    } else {
+        quote!(#ty)
+    }
+}
+
+/// The parameter type of a field's setter and the expression that turns the
+/// parameter `value` into the contents of the builder slot.
+fn setter_value(info: &FieldInfo) -> (TokenStream2, TokenStream2) {
+    let ty = setter_ty(info);
+
+    let (param_ty, value) = if info.setter.into {
+        (quote!(impl core::convert::Into<#ty>), quote!(value.into()))
+    } else {
+        (ty, quote!(value))
+    };
+
+    // An optional slot holds `Some(None)` once the field is set to `None`.
+    let strip_option = info.is_optional && info.setter.strip_option;
+    let slot_value = match (info.has_explicit_none(), strip_option) {
+        (true, true) => quote!(core::option::Option::Some(core::option::Option::Some(#value))),
+        (false, false) if info.is_optional => value,
+        _ => quote!(core::option::Option::Some(#value)),
+    };
+
+    (param_ty, slot_value)
+}
+
+fn field_setters(info: &FieldInfo, pattern: Pattern) -> TokenStream2 {
+    let ident = &info.ident;
     let ty = info.ty;
+    let vis = &info.vis;
+    let doc = &info.doc;
 
+    if info.setter.custom {
+        return TokenStream2::new();
+    }
+
+    let (receiver, ret, bind_builder) = pattern.setter_parts();
+    let must_use = pattern.must_use();
+    let reset = reset_and_unset(info, &must_use, &receiver, &ret, &bind_builder);
+
+    // The nested builder is modified in place instead.
+    if let Some((builder_ty, _)) = info.sub_builder_types() {
+        return quote! {
+            #doc
+            #vis fn #ident(&mut self) -> &mut #builder_ty {
+                self.#ident.get_or_insert_with(core::default::Default::default)
+            }
+
+            #reset
+        };
+    }
+
+    let (param_ty, slot_value) = setter_value(info);
+    let try_setter = try_setter(info, &must_use, &receiver, &ret);
     let mut one_at_a_time = TokenStream2::new();
     let mut all_at_once = quote! {
-        fn #ident(&mut self, value: #ty) -> &mut Self {
-            self.#ident = Some(value);
-            self
+        #doc
+        #must_use
+        #vis fn #ident(#receiver, value: #param_ty) -> #ret {
+            #bind_builder
+            builder.#ident = #slot_value;
+            builder
         }
+
+        #try_setter
     };
 
     if let Some(builder_attr_ident) = &info.builder_attr_ident {
-        if let Some(inner_ty) = get_inner_type(ty, "Vec") {
-            one_at_a_time = quote! {
-                fn #builder_attr_ident(&mut self, value: #inner_ty) -> &mut Self {
-                    self.#ident.get_or_insert_with(Vec::new).push(value);
-                    self
+        let into = |ty: &Type| {
+            if info.setter.into {
+                quote!(impl core::convert::Into<#ty>)
+            } else {
+                quote!(#ty)
+            }
+        };
+        let convert = |value: TokenStream2| {
+            if info.setter.into {
+                quote!(#value.into())
+            } else {
+                value
+            }
+        };
+
+        // The parameters of the setter adding one item, the pattern binding
+        // them from an item passed to `extend_`, the type of such an item and
+        // the item added to the collection.
+        let collection = collection_item(ty);
+        // Items of other collections are left to `Extend` without conversion.
+        let convert_items = info.setter.into && !matches!(collection, CollectionItem::Unknown);
+        let (generics, bounds, params, pattern, item_ty, item) = match collection {
+            CollectionItem::Single(item_ty) => {
+                let param_ty = into(item_ty);
+                (
+                    quote!(),
+                    quote!(),
+                    quote!(value: #param_ty),
+                    quote!(value),
+                    param_ty,
+                    convert(quote!(value)),
+                )
+            }
+            CollectionItem::Pair(key_ty, value_ty) => {
+                let (key_param_ty, value_param_ty) = (into(key_ty), into(value_ty));
+                let (key, value) = (convert(quote!(key)), convert(quote!(value)));
+                (
+                    quote!(),
+                    quote!(),
+                    quote!(key: #key_param_ty, value: #value_param_ty),
+                    quote!((key, value)),
+                    quote!((#key_param_ty, #value_param_ty)),
+                    quote!((#key, #value)),
+                )
+            }
+            CollectionItem::Unknown => (
+                quote!(<__Item>),
+                quote!(where #ty: core::iter::Extend<__Item>),
+                quote!(value: __Item),
+                quote!(value),
+                quote!(__Item),
+                quote!(value),
+            ),
+        };
+
+        // `extend_` takes any number of items at once, converting each of them
+        // like the setter adding one item.
+        let items = if convert_items {
+            quote! {
+                core::iter::Iterator::map(core::iter::IntoIterator::into_iter(items), |#pattern| #item)
+            }
+        } else {
+            quote!(items)
+        };
+        let extend_ident = quote::format_ident!("extend_{}", ident);
+        let clear_ident = quote::format_ident!("clear_{}", ident);
+        let extend_doc = format!("Adds every item of `items` to `{}`.", ident);
+        let clear_doc = format!("Empties `{}`, removing every item added so far.", ident);
+
+        one_at_a_time = quote! {
+            #doc
+            #must_use
+            #vis fn #builder_attr_ident #generics(#receiver, #params) -> #ret #bounds {
+                #bind_builder
+                core::iter::Extend::extend(
+                    builder.#ident.get_or_insert_with(core::default::Default::default),
+                    core::iter::once(#item),
+                );
+                builder
+            }
+
+            #[doc = #extend_doc]
+            #must_use
+            #vis fn #extend_ident #generics(
+                #receiver,
+                items: impl core::iter::IntoIterator<Item = #item_ty>,
+            ) -> #ret #bounds {
+                #bind_builder
+                core::iter::Extend::extend(
+                    builder.#ident.get_or_insert_with(core::default::Default::default),
+                    #items,
+                );
+                builder
+            }
+
+            #[doc = #clear_doc]
+            #must_use
+            #vis fn #clear_ident(#receiver) -> #ret {
+                #bind_builder
+                builder.#ident = core::option::Option::Some(core::default::Default::default());
+                builder
+            }
+        };
+
+        // The whole-collection setter adds to what was set before.
+        if info.append {
+            let value = convert(quote!(value));
+            let value_ty = setter_ty(info);
+            let mut extend = quote! {
+                core::iter::Extend::extend(
+                    builder.#ident.get_or_insert_with(core::default::Default::default),
+                    value,
+                );
+            };
+            // Appending `None` leaves the field as it was.
+            if info.is_optional && !info.setter.strip_option {
+                extend = quote! {
+                    if let core::option::Option::Some(value) = value {
+                        #extend
+                    }
+                };
+            }
+            all_at_once = quote! {
+                #doc
+                #must_use
+                #vis fn #ident(#receiver, value: #param_ty) -> #ret {
+                    #bind_builder
+                    let value: #value_ty = #value;
+                    #extend
+                    builder
                 }
+
+                #try_setter
             };
         }
 
-        if builder_attr_ident.to_string() == ident.to_string() {
+        if builder_attr_ident == ident {
             all_at_once = TokenStream2::new();
         }
     }
@@ -63,127 +599,1219 @@ fn field_setters(info: &FieldInfo) -> TokenStream2 {
         #one_at_a_time
 
         #all_at_once
+
+        #reset
     }
 }
 
-#[proc_macro_derive(Builder, attributes(builder))]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let ItemStruct { ident, fields, .. } = syn::parse_macro_input!(input as syn::ItemStruct);
-
-    if let syn::Fields::Named(FieldsNamed { named, .. }) = fields {
-        // Construct the field information.
-        let mut field_info = Vec::with_capacity(named.pairs().len());
-        for pair in named.pairs().into_iter() {
-            let field = pair.value();
-            let mut info = FieldInfo {
-                ident: field.ident.as_ref().expect("Field should be named."),
-                ty: &field.ty,
-                is_optional: false,
-                builder_attr_ident: None,
-            };
+/// The documentation of a field's `reset_` method.
+fn reset_doc(ident: &Ident) -> String {
+    format!(
+        "Resets `{}` to not being set, as if its setter had never been called.",
+        ident
+    )
+}
 
-            for attr in field.attrs.iter() {
-                if let Some(ident) = attr.path.get_ident() {
-                    if ident == "builder" {
-                        if let Ok(ExprAssign { left, right, .. }) = attr.parse_args::<ExprAssign>()
-                        {
-                            if let Expr::Path(ExprPath { ref path, .. }) = *left {
-                                if !path.is_ident("each") {
-                                    return Error::new(
-                                        attr.parse_meta().unwrap().span(),
-                                        "expected `builder(each = \"...\")`",
-                                    )
-                                    .into_compile_error()
-                                    .into();
-                                }
-
-                                if let Expr::Lit(ExprLit { lit, .. }) = *right {
-                                    if let Lit::Str(lit_str) = lit {
-                                        info.builder_attr_ident =
-                                            Some(quote::format_ident!("{}", lit_str.value()));
-                                    }
-                                }
-                            }
+/// Generates `reset_` to return a field to not being set and, for optional
+/// fields, `unset_` to set it to `None` explicitly.
+fn reset_and_unset(
+    info: &FieldInfo,
+    must_use: &TokenStream2,
+    receiver: &TokenStream2,
+    ret: &TokenStream2,
+    bind_builder: &TokenStream2,
+) -> TokenStream2 {
+    let ident = &info.ident;
+    let vis = &info.vis;
+    let reset_ident = quote::format_ident!("reset_{}", ident);
+    let reset_doc = reset_doc(ident);
+
+    let unset = info.has_explicit_none().then(|| {
+        let unset_ident = quote::format_ident!("unset_{}", ident);
+        let unset_doc = format!(
+            "Sets `{}` to `None`, overriding its default and its value in a \
+             builder this one is merged into.",
+            ident
+        );
+        quote! {
+            #[doc = #unset_doc]
+            #must_use
+            #vis fn #unset_ident(#receiver) -> #ret {
+                #bind_builder
+                builder.#ident = core::option::Option::Some(core::option::Option::None);
+                builder
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #reset_doc]
+        #must_use
+        #vis fn #reset_ident(#receiver) -> #ret {
+            #bind_builder
+            builder.#ident = core::option::Option::None;
+            builder
+        }
+
+        #unset
+    }
+}
+
+/// Generates the `try_` setter of a field, which converts its argument with
+/// `TryInto` before passing it on to the field's setter.
+fn try_setter(
+    info: &FieldInfo,
+    must_use: &TokenStream2,
+    receiver: &TokenStream2,
+    ret: &TokenStream2,
+) -> TokenStream2 {
+    if !info.setter.try_setter {
+        return TokenStream2::new();
+    }
+
+    let ident = &info.ident;
+    let vis = &info.vis;
+    let doc = &info.doc;
+    let try_ident = quote::format_ident!("try_{}", ident);
+    let ty = setter_ty(info);
+
+    quote! {
+        #doc
+        #must_use
+        #vis fn #try_ident<__Value>(
+            #receiver,
+            value: __Value,
+        ) -> core::result::Result<#ret, __Value::Error>
+        where
+            __Value: core::convert::TryInto<#ty>,
+        {
+            let value: #ty = core::convert::TryInto::try_into(value)?;
+            core::result::Result::Ok(self.#ident(value))
+        }
+    }
+}
+
+/// Generates `From<Target>` for the builder and `Target::to_builder()`, which
+/// turn an existing value back into a builder with every slot populated.
+///
+/// `to_builder()` is only callable when every field is `Clone`. The bounds are
+/// higher-ranked so that they are checked where it is called rather than
+/// rejected outright for fields that are not `Clone`.
+fn existing_value_conversions(
+    target: &Target,
+    field_info: &[FieldInfo],
+    builder_ty: &TokenStream2,
+    builder_state: &TokenStream2,
+) -> TokenStream2 {
+    let Target {
+        ident,
+        vis,
+        generics,
+        builder_ident,
+        marker,
+        ..
+    } = target;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let marker = marker.then(|| quote!(__target: core::marker::PhantomData,));
+
+    let populated_slots = |value: &dyn Fn(&Member) -> TokenStream2| {
+        TokenStream2::from_iter(field_info.iter().map(|info| {
+            let field_ident = &info.ident;
+            let slot = info.slot_from_value(value(&info.member));
+            quote!(#field_ident: #slot,)
+        }))
+    };
+    let moved_slots = populated_slots(&|member| quote!(value.#member));
+    let cloned_slots = populated_slots(&|member| quote!(core::clone::Clone::clone(&self.#member)));
+
+    let mut clone_bounds = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for FieldInfo { ty, .. } in field_info {
+        clone_bounds
+            .predicates
+            .push(syn::parse_quote!(for<'__a> #ty: core::clone::Clone));
+    }
+
+    quote! {
+        impl #impl_generics core::convert::From<#ident #ty_generics> for #builder_ty #where_clause {
+            fn from(value: #ident #ty_generics) -> Self {
+                #builder_ident {
+                    #moved_slots
+                    #marker
+                    #builder_state
+                }
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns a builder with every field set to a clone of this
+            /// value's, so that some of them can be changed before building a
+            /// new value.
+            #vis fn to_builder(&self) -> #builder_ty #clone_bounds {
+                #builder_ident {
+                    #cloned_slots
+                    #marker
+                    #builder_state
+                }
+            }
+        }
+    }
+}
+
+/// Adds one type parameter per state to the struct's generics. Their defaults
+/// are dropped, as they would no longer be trailing.
+fn with_state_params<'a>(generics: &Generics, states: impl Iterator<Item = &'a Ident>) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.params.iter_mut() {
+        match param {
+            GenericParam::Type(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Const(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+    }
+    generics
+        .params
+        .extend(states.map(|state| -> GenericParam { syn::parse_quote!(#state) }));
+    generics
+}
+
+/// The initial values of the builder's fields, with no field set.
+fn builder_defaults(field_info: &[FieldInfo], marker: bool) -> TokenStream2 {
+    let mut builder_defaults = TokenStream2::from_iter(
+        field_info
+            .iter()
+            .map(|FieldInfo { ident, .. }| quote!(#ident: core::option::Option::None,)),
+    );
+    if marker {
+        builder_defaults.extend(quote!(__target: core::marker::PhantomData,));
+    }
+    builder_defaults
+}
+
+/// Generates a builder that tracks which required fields have been set with
+/// one marker type parameter per required field, so that `build()` is only
+/// available once all of them are set.
+fn typestate_builder(
+    target: &Target,
+    builder_attrs: &TokenStream2,
+    field_info: &[FieldInfo],
+    builder_fields: &TokenStream2,
+    missing_fields: &TokenStream2,
+    checks: &TokenStream2,
+    build_body: &TokenStream2,
+) -> TokenStream2 {
+    let Target {
+        ident,
+        vis,
+        generics,
+        constructor,
+        builder_ident,
+        build_fn,
+        marker,
+        variant,
+        ..
+    } = target;
+    let build_error = target.build_error();
+    let (constructor_doc, build_fn_doc) = target.fn_docs();
+    let set_ident = quote::format_ident!("{}Set", builder_ident);
+    let unset_ident = quote::format_ident!("{}Unset", builder_ident);
+
+    let required = field_info
+        .iter()
+        .filter(|info| info.is_required())
+        .collect::<Vec<_>>();
+    let states = (0..required.len())
+        .map(|i| quote::format_ident!("__State{}", i))
+        .collect::<Vec<_>>();
+
+    // The struct's own generic arguments, without angle brackets.
+    let ty_args = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(LifetimeDef { lifetime, .. }) => quote!(#lifetime),
+            GenericParam::Type(TypeParam { ident, .. }) => quote!(#ident),
+            GenericParam::Const(ConstParam { ident, .. }) => quote!(#ident),
+        })
+        .collect::<Vec<_>>();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let builder_generics = with_state_params(generics, states.iter());
+    let (builder_impl_generics, builder_ty_generics, _) = builder_generics.split_for_impl();
+
+    // Setters for required fields move the builder into the next state. They
+    // can be called again to replace the value, e.g. after `to_builder()`.
+    let required_setters = TokenStream2::from_iter(
+        required
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| !info.setter.custom)
+            .map(|(i, info)| {
+                let field_ident = &info.ident;
+                let field_vis = &info.vis;
+                let field_doc = &info.doc;
+                let (param_ty, slot_value) = setter_value(info);
+                // The builder with this field's slot and state replaced.
+                let moved_builder = |slot_value: TokenStream2, state: &Ident| {
+                    let states_after = states.iter().enumerate().map(|(j, other)| {
+                        if i == j {
+                            quote!(#state)
+                        } else {
+                            quote!(#other)
+                        }
+                    });
+                    let moved_fields = field_info.iter().map(|FieldInfo { ident, .. }| {
+                        if ident == field_ident {
+                            quote!(#ident: #slot_value,)
+                        } else {
+                            quote!(#ident: self.#ident,)
+                        }
+                    });
+                    let moved_marker = marker.then(|| quote!(__target: self.__target,));
+                    let ty = quote!(#builder_ident<#(#ty_args,)* #(#states_after),*>);
+                    let value = quote! {
+                        #builder_ident {
+                            #(#moved_fields)*
+                            #moved_marker
+                            __state: core::marker::PhantomData,
+                        }
+                    };
+                    (ty, value)
+                };
+                let (next_builder, set_builder) = moved_builder(slot_value, &set_ident);
+                let (reset_builder, unset_builder) =
+                    moved_builder(quote!(core::option::Option::None), &unset_ident);
+                let must_use = Pattern::Owned.must_use();
+                let try_setter = try_setter(info, &must_use, &quote!(self), &next_builder);
+                let reset_ident = quote::format_ident!("reset_{}", field_ident);
+                let reset_doc = reset_doc(field_ident);
+
+                quote! {
+                    impl #builder_impl_generics #builder_ident #builder_ty_generics #where_clause {
+                        #field_doc
+                        #must_use
+                        #field_vis fn #field_ident(self, value: #param_ty) -> #next_builder {
+                            #set_builder
+                        }
+
+                        #try_setter
+
+                        #[doc = #reset_doc]
+                        #must_use
+                        #field_vis fn #reset_ident(self) -> #reset_builder {
+                            #unset_builder
                         }
                     }
                 }
+            }),
+    );
+
+    // All other setters are available in every state.
+    let other_setters = TokenStream2::from_iter(
+        field_info
+            .iter()
+            .filter(|info| !info.is_required())
+            .map(|info| field_setters(info, Pattern::Owned)),
+    );
+
+    let all_set = states.iter().map(|_| &set_ident).collect::<Vec<_>>();
+
+    // A value of an enum could be any of its variants.
+    let conversions = (!variant).then(|| {
+        existing_value_conversions(
+            target,
+            field_info,
+            &quote!(#builder_ident<#(#ty_args,)* #(#all_set),*>),
+            &quote!(__state: core::marker::PhantomData,),
+        )
+    });
+    let all_unset = states.iter().map(|_| &unset_ident).collect::<Vec<_>>();
+    let builder_defaults = builder_defaults(field_info, *marker);
+
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #[doc = #constructor_doc]
+            #vis fn #constructor() -> #builder_ident<#(#ty_args,)* #(#all_unset),*> {
+                core::default::Default::default()
+            }
+        }
+
+        /// Marks a required field of the builder as set.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        #vis struct #set_ident;
+
+        /// Marks a required field of the builder as not yet set.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        #vis struct #unset_ident;
+
+        #builder_attrs
+        #vis struct #builder_ident #builder_generics #where_clause {
+            #builder_fields
+            __state: core::marker::PhantomData<(#(#states,)*)>,
+        }
+
+        impl #impl_generics core::default::Default for #builder_ident<#(#ty_args,)* #(#all_unset),*> #where_clause {
+            fn default() -> Self {
+                #builder_ident {
+                    #builder_defaults
+                    __state: core::marker::PhantomData,
+                }
+            }
+        }
+
+        #required_setters
+
+        impl #builder_impl_generics #builder_ident #builder_ty_generics #where_clause {
+            #other_setters
+
+            #missing_fields
+        }
+
+        impl #impl_generics #builder_ident<#(#ty_args,)* #(#all_set),*> #where_clause {
+            #[doc = #build_fn_doc]
+            #[must_use = "the built value is returned rather than stored"]
+            #vis fn #build_fn(self) -> core::result::Result<#ident #ty_generics, #build_error> {
+                #checks
+
+                let builder = self;
+
+                #build_body
+            }
+        }
+
+        #conversions
+    }
+}
+
+/// Generates the builder, its error type and the constructor on the target
+/// for a set of fields.
+fn fields_builder(
+    target: &Target,
+    attrs: &[Attribute],
+    fields: &Fields,
+) -> syn::Result<TokenStream2> {
+    let mut errors = attrs::Errors::default();
+    let struct_info = attrs::parse_struct_attrs(attrs).unwrap_or_else(|err| {
+        errors.push(err);
+        StructInfo::default()
+    });
+
+    // Apply the names and visibility configured on the struct.
+    let target = &Target {
+        // The builder is as visible as the target unless configured otherwise.
+        vis: struct_info.vis.as_ref().unwrap_or(target.vis),
+        constructor: struct_info
+            .constructor
+            .clone()
+            .unwrap_or_else(|| target.constructor.clone()),
+        builder_ident: struct_info
+            .name
+            .clone()
+            .unwrap_or_else(|| target.builder_ident.clone()),
+        build_fn: struct_info
+            .build_fn
+            .clone()
+            .unwrap_or_else(|| target.build_fn.clone()),
+        build_error: struct_info.build_error.clone(),
+        ..target.clone()
+    };
+    let Target {
+        ident,
+        vis,
+        generics,
+        path,
+        constructor,
+        builder_ident,
+        build_fn,
+        variant,
+        ..
+    } = target;
+    let error_ident = target.error_ident();
+    let build_error = target.build_error();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // `String`, `Box` and `format!` come from `alloc` without std.
+    let alloc = if struct_info.no_std {
+        quote!(alloc)
+    } else {
+        quote!(std)
+    };
+
+    // Construct the field information.
+    let mut field_info = Vec::with_capacity(fields.len());
+    // Skipped fields are only initialized by `build()`.
+    let mut skipped_fields = TokenStream2::new();
+    for (i, field) in fields.iter().enumerate() {
+        // Tuple fields are set with `_0`, `_1`, ... unless renamed.
+        let (ident, member) = match &field.ident {
+            Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+            None => (quote::format_ident!("_{}", i), Member::Unnamed(i.into())),
+        };
+        let mut info = FieldInfo {
+            ident,
+            member,
+            ty: &field.ty,
+            is_optional: false,
+            optional: None,
+            builder_attr_ident: None,
+            default: None,
+            setter: struct_info.setter,
+            validate_with: None,
+            vis: (*vis).clone(),
+            append: false,
+            sub_builder: false,
+            sub_builder_options: SubBuilderOptions::default(),
+            skip: false,
+            // The setters are documented like the field.
+            doc: TokenStream2::from_iter(
+                field
+                    .attrs
+                    .iter()
+                    .filter(|attr| attr.path.is_ident("doc"))
+                    .map(|attr| attr.to_token_stream()),
+            ),
+        };
+
+        if let Err(err) = attrs::parse_field_attrs(&field.attrs, &mut info) {
+            errors.push(err);
+        }
+
+        // `#[builder(optional)]` overrides the detection of `Option<T>`.
+        let inner_ty = match info.optional {
+            None => option_inner_type(&field.ty),
+            Some(true) => {
+                let inner_ty = first_type_argument(&field.ty);
+                if inner_ty.is_none() {
+                    errors.push(Error::new_spanned(
+                        &field.ty,
+                        "`optional` requires an alias of `Option<T>` with a type argument",
+                    ));
+                }
+                inner_ty
             }
+            Some(false) => None,
+        };
+        if let Some(inner_ty) = inner_ty {
+            info.ty = inner_ty;
+            info.is_optional = true;
+        }
 
-            if let Some(inner_ty) = get_inner_type(&field.ty, "Option") {
-                info.ty = inner_ty;
-                info.is_optional = true;
+        if info.sub_builder && info.sub_builder_types().is_none() {
+            errors.push(Error::new_spanned(
+                info.ty,
+                "`sub_builder` requires a struct type that derives Builder",
+            ));
+        }
+
+        // The setters of a mergeable builder live next to its `merge` method.
+        if struct_info.merge && !info.skip && !info.setter.custom {
+            let setters = [Some(&info.ident), info.builder_attr_ident.as_ref()];
+            if let Some(setter) = setters
+                .into_iter()
+                .flatten()
+                .find(|setter| *setter == "merge")
+            {
+                errors.push(Error::new(
+                    setter.span(),
+                    "setter `merge` collides with the builder's `merge` method, \
+                     rename it with #[builder(name = \"...\")]",
+                ));
             }
+        }
 
+        if info.skip {
+            let (member, default) = (&info.member, &info.default);
+            skipped_fields.extend(quote!(#member: #default,));
+        } else {
             field_info.push(info);
         }
+    }
 
-        // Fields for the builder struct.
-        let builder_fields = TokenStream2::from_iter(
-            field_info
-                .iter()
-                .map(|FieldInfo { ident, ty, .. }| quote!(#ident: core::option::Option<#ty>,)),
-        );
+    // Skipped fields may be the only ones using some of the generics.
+    let target = &Target {
+        marker: target.marker || !skipped_fields.is_empty(),
+        ..target.clone()
+    };
+    let marker = target.marker;
 
-        // Initial builder values.
-        let builder_defaults = TokenStream2::from_iter(
-            field_info
-                .iter()
-                .map(|FieldInfo { ident, .. }| quote!(#ident: None,)),
+    // Report every malformed attribute at once.
+    errors.finish()?;
+
+    // Builders that can be deserialized from partial values.
+    let deserialize = struct_info.deserialize;
+
+    // Deserializes the slot of an optional field, keeping an explicit `null`.
+    let deserialize_explicit_none =
+        quote::format_ident!("__deserialize_explicit_none_{}", builder_ident);
+    let deserialize_explicit_none_fn = (deserialize
+        && field_info.iter().any(FieldInfo::has_explicit_none))
+    .then(|| {
+        quote! {
+            #[allow(non_snake_case)]
+            fn #deserialize_explicit_none<'de, __D, __T>(
+                deserializer: __D,
+            ) -> core::result::Result<core::option::Option<core::option::Option<__T>>, __D::Error>
+            where
+                __D: serde::Deserializer<'de>,
+                __T: serde::Deserialize<'de>,
+            {
+                core::result::Result::map(
+                    <core::option::Option<__T> as serde::Deserialize<'de>>::deserialize(deserializer),
+                    core::option::Option::Some,
+                )
+            }
+        }
+    });
+
+    // Fields for the builder struct.
+    let mut builder_fields = TokenStream2::from_iter(field_info.iter().map(|info| {
+        let ident = &info.ident;
+        let slot_ty = info.slot_ty();
+        // Values are deserialized by the name of the field, not its setter.
+        let rename = match &info.member {
+            Member::Named(member) if deserialize && member != ident => {
+                let member = member.to_string();
+                quote!(#[serde(rename = #member)])
+            }
+            _ => quote!(),
+        };
+        // A `null` sets an optional field to `None` rather than leaving it unset.
+        let explicit_none = (deserialize && info.has_explicit_none()).then(|| {
+            let ty = info.ty;
+            let deserialize_with = deserialize_explicit_none.to_string();
+            let bound = format!("{}: serde::Deserialize<'de>", ty.to_token_stream());
+            quote!(#[serde(deserialize_with = #deserialize_with, bound(deserialize = #bound))])
+        });
+        quote! {
+            #rename
+            #explicit_none
+            #ident: core::option::Option<#slot_ty>,
+        }
+    }));
+
+    let builder_defaults = builder_defaults(&field_info, marker);
+
+    if marker {
+        let skip = deserialize.then(|| quote!(#[serde(skip)]));
+        builder_fields.extend(
+            quote!(#skip __target: core::marker::PhantomData<fn() -> #ident #ty_generics>,),
         );
+    }
 
-        // Builder setters.
-        let setters = TokenStream2::from_iter(field_info.iter().map(field_setters));
-
-        // Body of the build() method.
-        let build_body = TokenStream2::from_iter(field_info.iter().map(
-            |FieldInfo {
-                 ident,
-                 is_optional,
-                 builder_attr_ident,
-                 ..
-             }| {
-                let value_suffix = if *is_optional {
-                    quote!()
-                } else if builder_attr_ident.is_some() {
-                    quote!(.unwrap_or_default())
-                } else {
-                    let field_name = ident.to_string();
-                    quote!(.expect(&format!("{} should have a value.", #field_name)))
-                };
+    // Builder setters.
+    let pattern = struct_info.pattern.unwrap_or_default();
+    let setters =
+        TokenStream2::from_iter(field_info.iter().map(|info| field_setters(info, pattern)));
 
-                quote!(#ident: self.#ident.take()#value_suffix,)
+    // Required fields that haven't been set, including those of nested
+    // builders, are all reported together by their path.
+    let missing_pushes = TokenStream2::from_iter(field_info.iter().map(|info| {
+        let ident = &info.ident;
+        let field_name = info.name();
+        let nested_path = quote!(&#alloc::format!("{}{}.", path, #field_name));
+        if !info.sub_builder {
+            if !info.is_required() {
+                return quote!();
+            }
+            quote! {
+                if self.#ident.is_none() {
+                    missing.push(#alloc::format!("{}{}", path, #field_name));
+                }
+            }
+        } else if info.is_optional {
+            // An optional nested value is only built if its builder was used.
+            quote! {
+                if let core::option::Option::Some(nested) = &self.#ident {
+                    nested.__missing_fields(#nested_path, missing);
+                }
+            }
+        } else {
+            quote! {
+                self.#ident
+                    .as_ref()
+                    .unwrap_or(&core::default::Default::default())
+                    .__missing_fields(#nested_path, missing);
+            }
+        }
+    }));
+    let missing_fields = quote! {
+        #[doc(hidden)]
+        #[allow(unused_variables)]
+        #vis fn __missing_fields(
+            &self,
+            path: &str,
+            missing: &mut #alloc::vec::Vec<#alloc::string::String>,
+        ) {
+            #missing_pushes
+        }
+    };
+    let missing_checks = if missing_pushes.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            let mut missing = #alloc::vec::Vec::new();
+            self.__missing_fields("", &mut missing);
+            if !missing.is_empty() {
+                return core::result::Result::Err(core::convert::From::from(
+                    #error_ident::MissingFields(missing),
+                ));
+            }
+        }
+    };
+
+    // Field validation, run before any value is moved out.
+    let field_validations = field_info.iter().filter_map(|info| {
+        let field_ident = &info.ident;
+        let field_name = info.name();
+        let pattern = if info.has_explicit_none() {
+            quote!(core::option::Option::Some(core::option::Option::Some(
+                value
+            )))
+        } else {
+            quote!(core::option::Option::Some(value))
+        };
+        info.validate_with.as_ref().map(|validate_with| {
+            quote! {
+                if let #pattern = &self.#field_ident {
+                    if let core::result::Result::Err(err) = #validate_with(value) {
+                        return core::result::Result::Err(core::convert::From::from(
+                            #error_ident::InvalidField {
+                                field: #field_name,
+                                message: #alloc::string::ToString::to_string(&err),
+                            },
+                        ));
+                    }
+                }
+            }
+        })
+    });
+    let validations = quote!(#(#field_validations)*);
+
+    // The built value is validated as a whole. A mutable builder gets the
+    // values back when it is rejected, so that it can still be fixed.
+    let restore = (pattern == Pattern::Mutable).then(|| {
+        let bindings = field_info.iter().map(|FieldInfo { ident, member, .. }| {
+            let binding = quote::format_ident!("__{}", ident);
+            quote!(#member: #binding,)
+        });
+        let restored_slots = field_info.iter().map(|info| {
+            let ident = &info.ident;
+            let slot =
+                info.slot_from_value(quote::format_ident!("__{}", ident).into_token_stream());
+            quote!(builder.#ident = #slot;)
+        });
+        quote! {
+            match value {
+                #path { #(#bindings)* .. } => {
+                    #(#restored_slots)*
+                }
+                #[allow(unreachable_patterns)]
+                _ => {}
+            }
+        }
+    });
+    let struct_validation = struct_info.validate.as_ref().map(|validate| {
+        quote! {
+            if let core::result::Result::Err(err) = #validate(&value) {
+                let message = #alloc::string::ToString::to_string(&err);
+                #restore
+                return core::result::Result::Err(core::convert::From::from(
+                    #error_ident::Validation(message),
+                ));
+            }
+        }
+    });
+
+    // Nested builders are built first, failing with the path of the field. A
+    // mutable builder builds them in place and gets back those built so far
+    // when one fails, so that it can still be fixed.
+    let mut nested_builds = TokenStream2::new();
+    let mut restore_nested = TokenStream2::new();
+    for info in &field_info {
+        let (nested_builder, nested_error) = match info.sub_builder_types() {
+            Some(types) => types,
+            None => continue,
+        };
+        // Errors about the nested builder point at the field's type.
+        let span = match nested_builder.segments.last() {
+            Some(segment) => segment.ident.span(),
+            None => continue,
+        };
+        let build_fn = match &info.sub_builder_options.build_fn {
+            Some(build_fn) => build_fn.clone(),
+            None => Ident::new("build", span),
+        };
+        let sub_builder = Ident::new("__sub_builder", span);
+        let field_ident = &info.ident;
+        let field_name = info.name();
+        let nested_ident = quote::format_ident!("__{}", field_ident);
+
+        let nested_value = quote! {
+            match nested.#sub_builder().#build_fn() {
+                core::result::Result::Ok(value) => value,
+                core::result::Result::Err(err) => {
+                    fn nest(error: #nested_error) -> #error_ident {
+                        match error {
+                            #nested_error::MissingFields(fields) => #error_ident::MissingFields(fields),
+                            #nested_error::InvalidField { field, message } => {
+                                #error_ident::InvalidField { field, message }
+                            }
+                            #nested_error::Validation(message) => #error_ident::Validation(message),
+                            #nested_error::Nested { field, error } => #error_ident::Nested {
+                                field,
+                                error: #alloc::boxed::Box::new(nest(*error)),
+                            },
+                        }
+                    }
+
+                    #restore_nested
+                    return core::result::Result::Err(core::convert::From::from(
+                        #error_ident::Nested {
+                            field: #field_name,
+                            error: #alloc::boxed::Box::new(nest(err)),
+                        },
+                    ));
+                }
+            }
+        };
+
+        // An optional field is only built if its builder was used.
+        nested_builds.extend(match (pattern, info.is_optional) {
+            (Pattern::Mutable, true) => quote! {
+                let #nested_ident = match &mut builder.#field_ident {
+                    core::option::Option::Some(nested) => {
+                        core::option::Option::Some(#nested_value)
+                    }
+                    core::option::Option::None => core::option::Option::None,
+                };
+            },
+            (Pattern::Mutable, false) => quote! {
+                let nested = builder
+                    .#field_ident
+                    .get_or_insert_with(core::default::Default::default);
+                let #nested_ident = #nested_value;
             },
-        ));
+            (_, true) => quote! {
+                let #nested_ident = match builder.#field_ident {
+                    core::option::Option::Some(mut nested) => {
+                        core::option::Option::Some(#nested_value)
+                    }
+                    core::option::Option::None => core::option::Option::None,
+                };
+            },
+            (_, false) => quote! {
+                let mut nested = builder.#field_ident.unwrap_or_default();
+                let #nested_ident = #nested_value;
+            },
+        });
 
-        let builder_ident = quote::format_ident!("{}Builder", ident);
-        let implementation = quote! {
-            impl #ident {
-                pub fn builder() -> #builder_ident {
-                    #builder_ident {
-                        #builder_defaults
+        if pattern == Pattern::Mutable {
+            let slot = info.slot_from_value(nested_ident.to_token_stream());
+            restore_nested.extend(quote!(builder.#field_ident = #slot;));
+        }
+    }
+
+    // Body of the build() method.
+    let build_fields = TokenStream2::from_iter(field_info.iter().map(
+        |FieldInfo {
+             ident,
+             member,
+             is_optional,
+             builder_attr_ident,
+             default,
+             sub_builder,
+             ..
+         }| {
+            if *sub_builder {
+                let nested_ident = quote::format_ident!("__{}", ident);
+                return quote!(#member: #nested_ident,);
+            }
+
+            let value_suffix = if let Some(default) = default {
+                if *is_optional && builder_attr_ident.is_some() {
+                    quote!(.map(core::option::Option::Some).unwrap_or_else(|| #default))
+                } else {
+                    quote!(.unwrap_or_else(|| #default))
+                }
+            } else if *is_optional && builder_attr_ident.is_some() {
+                quote!()
+            } else if *is_optional {
+                // An explicit `None` and an unset field both build `None`.
+                quote!(.flatten())
+            } else if builder_attr_ident.is_some() {
+                quote!(.unwrap_or_default())
+            } else {
+                // Presence was already checked above.
+                quote!(.unwrap())
+            };
+
+            let value = pattern.take_value(ident);
+            quote!(#member: #value #value_suffix,)
+        },
+    ));
+    let build_body = quote! {
+        #nested_builds
+
+        let value = #path {
+            #build_fields
+            #skipped_fields
+        };
+
+        #struct_validation
+
+        core::result::Result::Ok(value)
+    };
+
+    // Derives and attributes forwarded onto the builder.
+    let mut derives = struct_info.derives.clone();
+    // Default is always implemented, so deriving it is a no-op.
+    derives.retain(|derive| {
+        !derive
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Default")
+    });
+    let derives_clone = derives.iter().any(|derive| {
+        derive
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Clone")
+    });
+    if pattern == Pattern::Immutable && !derives_clone {
+        // Immutable setters clone the builder.
+        derives.push(syn::parse_quote!(core::clone::Clone));
+    }
+    let derives_deserialize = derives.iter().any(|derive| {
+        derive
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Deserialize")
+    });
+    if deserialize && !derives_deserialize {
+        derives.push(syn::parse_quote!(serde::Deserialize));
+    }
+    let builder_derives = (!derives.is_empty()).then(|| quote!(#[derive(#(#derives),*)]));
+    // Fields missing from a partial value are left unset.
+    let serde_default = deserialize.then(|| quote!(#[serde(default)]));
+    let forwarded_attrs = &struct_info.attrs;
+    let builder_doc = match &struct_info.doc {
+        Some(doc) => doc.value(),
+        None => format!("Builder for [`{}`].", target.doc_path()),
+    };
+    let builder_attrs = quote! {
+        #[doc = #builder_doc]
+        #builder_derives
+        #serde_default
+        #(#forwarded_attrs)*
+    };
+
+    let (build_receiver, bind_builder, build_bounds) = pattern.build_parts();
+    let (constructor_doc, build_fn_doc) = target.fn_docs();
+
+    // Fields set in the other builder win, nested builders are merged.
+    let merged_fields = TokenStream2::from_iter(field_info.iter().map(|info| {
+        let ident = &info.ident;
+        if info.sub_builder {
+            quote! {
+                match (&mut self.#ident, other.#ident) {
+                    (core::option::Option::Some(this), core::option::Option::Some(other)) => {
+                        this.merge(other);
                     }
+                    (this, other @ core::option::Option::Some(_)) => *this = other,
+                    _ => {}
+                }
+            }
+        } else {
+            quote! {
+                if other.#ident.is_some() {
+                    self.#ident = other.#ident;
+                }
+            }
+        }
+    }));
+    let merge = struct_info.merge.then(|| {
+        quote! {
+            /// Sets every field that is set in `other` to its value there,
+            /// merging nested builders field by field.
+            #vis fn merge(&mut self, other: Self) -> &mut Self {
+                #merged_fields
+                self
+            }
+        }
+    });
+    // A value of an enum could be any of its variants.
+    let conversions = (!variant).then(|| {
+        existing_value_conversions(
+            target,
+            &field_info,
+            &quote!(#builder_ident #ty_generics),
+            &quote!(),
+        )
+    });
+    // Only builders modified in place and failing with the generated error can
+    // be nested, which is checked by calling this when building the outer one.
+    let nestable = (pattern == Pattern::Mutable && struct_info.build_error.is_none()).then(|| {
+        quote! {
+            #[doc(hidden)]
+            #vis fn __sub_builder(&mut self) -> &mut Self {
+                self
+            }
+        }
+    });
+    let builder = if struct_info.typestate {
+        typestate_builder(
+            target,
+            &builder_attrs,
+            &field_info,
+            &builder_fields,
+            &missing_fields,
+            &quote!(#missing_checks #validations),
+            &build_body,
+        )
+    } else {
+        quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #[doc = #constructor_doc]
+                #vis fn #constructor() -> #builder_ident #ty_generics {
+                    core::default::Default::default()
                 }
             }
 
-            pub struct #builder_ident {
+            #builder_attrs
+            #vis struct #builder_ident #generics #where_clause {
                 #builder_fields
             }
 
-            impl #builder_ident {
+            impl #impl_generics core::default::Default for #builder_ident #ty_generics #where_clause {
+                fn default() -> Self {
+                    #builder_ident {
+                        #builder_defaults
+                    }
+                }
+            }
+
+            impl #impl_generics #builder_ident #ty_generics #where_clause {
                 #setters
 
-                pub fn build(&mut self) -> std::result::Result<#ident, std::boxed::Box<dyn std::error::Error>> {
-                    Ok(#ident {
-                        #build_body
-                    })
+                #merge
+
+                #missing_fields
+
+                #nestable
+
+                #[doc = #build_fn_doc]
+                #[must_use = "the built value is returned rather than stored"]
+                #vis fn #build_fn(#build_receiver) -> core::result::Result<#ident #ty_generics, #build_error>
+                #build_bounds
+                {
+                    #missing_checks
+
+                    #validations
+
+                    #bind_builder
+
+                    #build_body
                 }
             }
-        };
 
-        implementation.into()
-    } else {
-        return Error::new(ident.span(), "#[derive(Builder)] requires named fields.")
-            .into_compile_error()
-            .into();
+            #conversions
+        }
+    };
+
+    let error_doc = format!(
+        "The error returned when building a [`{}`] fails.",
+        target.doc_path()
+    );
+    let error_impl =
+        (!struct_info.no_std).then(|| quote!(impl std::error::Error for #error_ident {}));
+
+    Ok(quote! {
+        #builder
+
+        #deserialize_explicit_none_fn
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error_ident {
+            /// Required fields were not set before calling `build()`, named by
+            /// their path, e.g. `inner.port` for a field of a nested builder.
+            MissingFields(#alloc::vec::Vec<#alloc::string::String>),
+            /// A field's `validate_with` function rejected its value.
+            InvalidField {
+                /// The name of the field.
+                field: &'static str,
+                /// Why the value was rejected.
+                message: #alloc::string::String,
+            },
+            /// The builder's `validate` function rejected it.
+            Validation(#alloc::string::String),
+            /// The nested builder of a `sub_builder` field failed.
+            Nested {
+                /// The name of the field.
+                field: &'static str,
+                /// The error of the nested builder.
+                error: #alloc::boxed::Box<#error_ident>,
+            },
+        }
+
+        impl #error_ident {
+            /// Formats the error of a nested builder, with `path` being the
+            /// fields leading to it, e.g. `outer.inner.`.
+            fn fmt_nested(&self, path: &str, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self {
+                    #error_ident::MissingFields(fields) => {
+                        let plural = fields.len() > 1;
+                        f.write_str(if plural { "fields " } else { "field " })?;
+                        for (i, field) in fields.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(", ")?;
+                            }
+                            write!(f, "`{}{}`", path, field)?;
+                        }
+                        f.write_str(if plural { " were not set" } else { " was not set" })
+                    }
+                    #error_ident::InvalidField { field, message } => {
+                        write!(f, "invalid value for field `{}{}`: {}", path, field, message)
+                    }
+                    #error_ident::Validation(message) if path.is_empty() => {
+                        write!(f, "validation failed: {}", message)
+                    }
+                    #error_ident::Validation(message) => {
+                        let path = path.trim_end_matches('.');
+                        write!(f, "validation failed for `{}`: {}", path, message)
+                    }
+                    #error_ident::Nested { field, error } => {
+                        error.fmt_nested(&#alloc::format!("{}{}.", path, field), f)
+                    }
+                }
+            }
+        }
+
+        impl core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                self.fmt_nested("", f)
+            }
+        }
+
+        #error_impl
+    })
+}
+
+/// Converts a `CamelCase` variant name into `snake_case`. A run of capitals is
+/// one word, so `HTTPRequest` becomes `http_request`.
+fn to_snake_case(ident: &Ident) -> String {
+    let chars = ident.to_string().chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let after_lower = !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let ends_run = chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_lower || ends_run {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
     }
+    snake
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident,
+        vis,
+        generics,
+        attrs,
+        data,
+        ..
+    } = syn::parse_macro_input!(input as DeriveInput);
+
+    let result = match data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            let target = Target {
+                ident: &ident,
+                vis: &vis,
+                generics: &generics,
+                path: quote!(#ident),
+                constructor: quote::format_ident!("builder"),
+                builder_ident: quote::format_ident!("{}Builder", ident),
+                build_fn: quote::format_ident!("build"),
+                build_error: None,
+                marker: false,
+                variant: false,
+            };
+            fields_builder(&target, &attrs, &fields)
+        }
+        // One builder per struct-like variant.
+        Data::Enum(DataEnum { variants, .. })
+            if variants
+                .iter()
+                .any(|variant| matches!(variant.fields, Fields::Named(_))) =>
+        {
+            let mut errors = attrs::Errors::default();
+            // Options on the enum apply to every variant's builder.
+            let enum_options = attrs::parse_enum_options(&attrs).unwrap_or_else(|err| {
+                errors.push(err);
+                Vec::new()
+            });
+            let mut builders = TokenStream2::new();
+            for variant in variants.iter() {
+                if let Fields::Named(_) = &variant.fields {
+                    let variant_ident = &variant.ident;
+                    let builder_ident = quote::format_ident!("{}{}Builder", ident, variant_ident);
+                    let target = Target {
+                        ident: &ident,
+                        vis: &vis,
+                        generics: &generics,
+                        path: quote!(#ident::#variant_ident),
+                        constructor: quote::format_ident!(
+                            "{}_builder",
+                            to_snake_case(variant_ident)
+                        ),
+                        builder_ident,
+                        build_fn: quote::format_ident!("build"),
+                        build_error: None,
+                        marker: true,
+                        variant: true,
+                    };
+
+                    let variant_attrs = attrs::variant_attrs(&enum_options, &variant.attrs);
+                    match fields_builder(&target, &variant_attrs, &variant.fields) {
+                        Ok(builder) => builders.extend(builder),
+                        Err(err) => errors.push(err),
+                    }
+                } else {
+                    // Other variants get no builder to configure.
+                    let field_attrs = variant.fields.iter().flat_map(|field| &field.attrs);
+                    for attr in variant.attrs.iter().chain(field_attrs) {
+                        if attr.path.is_ident("builder") {
+                            errors.push(Error::new_spanned(
+                                attr,
+                                "`#[builder]` requires a variant with named fields",
+                            ));
+                        }
+                    }
+                }
+            }
+            errors.finish().map(|()| builders)
+        }
+        Data::Enum(_) => Err(Error::new(
+            ident.span(),
+            "#[derive(Builder)] requires named fields.",
+        )),
+        Data::Union(_) => Err(Error::new(
+            ident.span(),
+            "#[derive(Builder)] does not support unions.",
+        )),
+    };
+
+    result.unwrap_or_else(Error::into_compile_error).into()
 }
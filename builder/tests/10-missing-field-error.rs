@@ -0,0 +1,37 @@
+// Instead of panicking when a required field was never set, the generated
+// build() method should return a dedicated error type named after the builder.
+// The error identifies which fields are missing so that callers can report it
+// without having to catch a panic.
+//
+// Optional fields and fields with an `each` attribute are never reported as
+// missing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.arg("build".to_owned());
+
+    let err = builder.build().err().unwrap();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec!["executable".to_owned()])
+    );
+    assert_eq!(err.to_string(), "field `executable` was not set");
+
+    // The builder can still be completed after a failed build.
+    let command = builder.executable("cargo".to_owned()).build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert!(command.current_dir.is_none());
+
+    let _: &dyn std::error::Error = &err;
+}
@@ -0,0 +1,69 @@
+// The builder should work for structs with generic parameters. Every kind of
+// generic parameter, along with any bounds and where-clauses, must be carried
+// over onto the builder struct, its impl block and the builder() constructor.
+//
+//
+// Resources:
+//
+//   - Splitting generics for use in an impl block:
+//     https://docs.rs/syn/1.0/syn/struct.Generics.html#method.split_for_impl
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+pub trait Transport {
+    fn name(&self) -> &str;
+}
+
+pub struct Tcp;
+
+impl Transport for Tcp {
+    fn name(&self) -> &str {
+        "tcp"
+    }
+}
+
+#[derive(Builder)]
+pub struct Conn<'a, T: Transport> {
+    host: &'a str,
+    transport: T,
+    #[builder(each = "tag")]
+    tags: Vec<&'a str>,
+}
+
+#[derive(Builder)]
+pub struct Buffer<const N: usize> {
+    bytes: [u8; N],
+    label: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Labeled<K, V>
+where
+    K: Debug + Clone,
+    V: Default,
+{
+    key: K,
+    value: V,
+}
+
+fn main() {
+    let host = String::from("localhost");
+    let conn = Conn::builder()
+        .host(&host)
+        .transport(Tcp)
+        .tag("primary")
+        .build()
+        .unwrap();
+    assert_eq!(conn.host, "localhost");
+    assert_eq!(conn.transport.name(), "tcp");
+    assert_eq!(conn.tags, vec!["primary"]);
+
+    let buffer = Buffer::<4>::builder().bytes([1, 2, 3, 4]).build().unwrap();
+    assert_eq!(buffer.bytes, [1, 2, 3, 4]);
+    assert!(buffer.label.is_none());
+
+    let labeled = Labeled::builder().key("key").value(1u8).build().unwrap();
+    assert_eq!(labeled.key, "key");
+    assert_eq!(labeled.value, 1);
+}
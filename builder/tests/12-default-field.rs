@@ -0,0 +1,50 @@
+// Fields annotated with #[builder(default)] are no longer required: when left
+// unset, build() falls back to Default::default(). An arbitrary expression can
+// be given instead with #[builder(default = ...)]; it is only evaluated when
+// the field was not set.
+//
+// For Option fields the default expression has the type of the whole field,
+// so it may be None or Some(...).
+
+use derive_builder::Builder;
+
+fn default_dir() -> Option<String> {
+    Some("/tmp".to_owned())
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(default = 3)]
+    retries: u32,
+    #[builder(default = String::from("sh"))]
+    shell: String,
+    #[builder(default = default_dir())]
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.retries, 3);
+    assert_eq!(command.shell, "sh");
+    assert_eq!(command.current_dir.as_deref(), Some("/tmp"));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned()])
+        .retries(0)
+        .shell("bash".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.retries, 0);
+    assert_eq!(command.shell, "bash");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
@@ -0,0 +1,65 @@
+// With #[builder(typestate)] on the struct, the builder keeps track of which
+// required fields have been set in its type. Each required field gets a marker
+// type parameter that is either CommandBuilderUnset or CommandBuilderSet, and
+// build() is only implemented once every marker is CommandBuilderSet.
+//
+// Setters consume the builder and return it in its new state, so the builder
+// is used by chaining method calls. Setting a required field again replaces
+// its value. Optional, defaulted and `each` fields can be set in any state.
+//
+// The marker parameters come after the struct's own generic parameters, which
+// may have defaults.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command<'a> {
+    executable: &'a str,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(default = 3)]
+    retries: u32,
+    current_dir: Option<String>,
+    timeout: u64,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Retry<T = u8> {
+    attempts: T,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .timeout(10)
+        .current_dir("..".to_owned())
+        .executable("cargo")
+        .arg("--release".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.retries, 3);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, 10);
+
+    // The state is part of the builder's type.
+    let builder: CommandBuilder<CommandBuilderSet, CommandBuilderUnset> =
+        Command::builder().executable("cargo");
+    let command = builder.timeout(5).build().unwrap();
+    assert_eq!(command.timeout, 5);
+
+    let command = Command::builder()
+        .executable("cargo")
+        .timeout(5)
+        .timeout(20)
+        .build()
+        .unwrap();
+    assert_eq!(command.timeout, 20);
+
+    let retry: Retry = Retry::builder().attempts(3).build().unwrap();
+    assert_eq!(retry.attempts, 3u8);
+}
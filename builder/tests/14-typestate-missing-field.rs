@@ -0,0 +1,20 @@
+// Forgetting a required field of a typestate builder is a compile error rather
+// than a runtime error: build() does not exist until every required field has
+// been set.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    timeout: u64,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let _command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .build();
+}
@@ -0,0 +1,65 @@
+// With #[builder(setter(into))] a setter accepts anything that converts into
+// the field's type, so callers can pass a &str to a String field. Setters of
+// Option<T> fields accept a plain T by default; #[builder(setter(strip_option =
+// false))] makes them accept the Option<T> itself instead, and together with
+// `into` they accept either a T or an Option<T>.
+//
+// Both options may be given on the struct to apply to every field, and each
+// field can override them.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(setter(into = false))]
+    timeout: u64,
+    #[builder(setter(strip_option = false))]
+    current_dir: Option<String>,
+    user: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(setter(strip_option = false))]
+pub struct Limits {
+    #[builder(setter(into))]
+    memory: Option<u64>,
+    #[builder(setter(strip_option))]
+    cpus: Option<u32>,
+    #[builder(setter(into = true, strip_option = true))]
+    name: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .timeout(10)
+        .current_dir(None::<String>)
+        .user("root")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.timeout, 10);
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.user.as_deref(), Some("root"));
+
+    let limits = Limits::builder()
+        .memory(1024)
+        .cpus(4)
+        .name("small")
+        .build()
+        .unwrap();
+    assert_eq!(limits.memory, Some(1024));
+    assert_eq!(limits.cpus, Some(4));
+    assert_eq!(limits.name.as_deref(), Some("small"));
+
+    let limits = Limits::builder().memory(None).build().unwrap();
+    assert_eq!(limits.memory, None);
+}
@@ -0,0 +1,55 @@
+// The way the builder is passed around can be chosen with
+// #[builder(pattern = "...")] on the struct:
+//
+//   - "mutable" (the default): setters and build() take &mut self.
+//   - "owned": setters and build() take self by value, so a builder can be
+//     created, filled in and built in one expression and moved around freely.
+//   - "immutable": setters and build() take &self and work on a clone of the
+//     builder, so a partially filled builder can be reused as a template.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Owned {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Immutable {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Mutable {
+    executable: String,
+}
+
+fn main() {
+    let owned = Owned::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(owned.executable, "cargo");
+    assert_eq!(owned.args, vec!["build"]);
+    assert!(owned.current_dir.is_none());
+
+    let template = Immutable::builder().executable("cargo".to_owned());
+    let build = template.arg("build".to_owned()).build().unwrap();
+    let test = template.arg("test".to_owned()).build().unwrap();
+    assert_eq!(build.args, vec!["build"]);
+    assert_eq!(test.args, vec!["test"]);
+    assert_eq!(template.build().unwrap().executable, "cargo");
+
+    let mut builder = Mutable::builder();
+    builder.executable("cargo".to_owned());
+    assert_eq!(builder.build().unwrap().executable, "cargo");
+}
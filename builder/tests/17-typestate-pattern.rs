@@ -0,0 +1,13 @@
+// Typestate builders change their type in every required setter, which is
+// only possible when the builder is passed by value. Asking for any other
+// pattern is an error.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate, pattern = "mutable")]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
@@ -0,0 +1,190 @@
+// Invariants of the built struct can be checked inside build().
+//
+// A field annotated with #[builder(validate_with = path)] has its value passed
+// by reference to the given function whenever it was set. A struct annotated
+// with #[builder(validate = "path")] has the built struct passed by reference
+// to the given function, so it also sees the values of fields that were left
+// to their default. The functions return a Result whose error is turned into a
+// message with ToString, and build() reports it through the InvalidField or
+// Validation variant of the builder error. On an enum, the function is given
+// the enum value built by any of the variants' builders.
+//
+// A mutable builder keeps its values when build() fails, so it can still be
+// fixed and built again.
+
+use derive_builder::Builder;
+
+fn nonzero(port: &u16) -> Result<(), &'static str> {
+    if *port == 0 {
+        Err("port must not be 0")
+    } else {
+        Ok(())
+    }
+}
+
+mod checks {
+    pub fn not_empty(host: &String) -> Result<(), String> {
+        if host.is_empty() {
+            Err("host is empty".to_owned())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn consistent(server: &Server) -> Result<(), String> {
+    if server.tls == Some(true) && server.port == 80 {
+        Err("tls cannot be served on port 80".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder)]
+#[builder(validate = "consistent")]
+pub struct Server {
+    #[builder(validate_with = checks::not_empty)]
+    host: String,
+    #[builder(validate_with = "nonzero", default = 80)]
+    port: u16,
+    #[builder(validate_with = nonzero)]
+    admin_port: Option<u16>,
+    tls: Option<bool>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned", validate = "owned_is_valid")]
+pub struct Owned {
+    value: u32,
+}
+
+fn owned_is_valid(owned: &Owned) -> Result<(), String> {
+    if owned.value > 10 {
+        Err(format!("{} is too large", owned.value))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder)]
+#[builder(validate = "positive")]
+pub enum Shape {
+    Circle { radius: u32 },
+    Square { side: u32 },
+}
+
+fn positive(shape: &Shape) -> Result<(), &'static str> {
+    match shape {
+        Shape::Circle { radius: 0 } | Shape::Square { side: 0 } => Err("empty shape"),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Typed {
+    #[builder(validate_with = nonzero)]
+    port: u16,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(443)
+        .tls(true)
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 443);
+
+    let err = Server::builder()
+        .host("localhost".to_owned())
+        .port(0)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        ServerBuilderError::InvalidField {
+            field: "port",
+            message: "port must not be 0".to_owned(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid value for field `port`: port must not be 0"
+    );
+
+    let err = Server::builder()
+        .host(String::new())
+        .port(8080)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for field `host`: host is empty"
+    );
+
+    let err = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .admin_port(0)
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        ServerBuilderError::InvalidField {
+            field: "admin_port",
+            ..
+        }
+    ));
+
+    let err = Server::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .tls(true)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        ServerBuilderError::Validation("tls cannot be served on port 80".to_owned())
+    );
+    assert_eq!(
+        err.to_string(),
+        "validation failed: tls cannot be served on port 80"
+    );
+
+    // The default port is validated too, and the builder can be fixed.
+    let mut builder = Server::builder();
+    builder.host("localhost".to_owned()).tls(true);
+    let err = builder.build().err().unwrap();
+    assert!(matches!(err, ServerBuilderError::Validation(_)));
+    let server = builder.port(443).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!((server.port, server.tls), (443, Some(true)));
+
+    assert!(Owned::builder().value(3).build().is_ok());
+    assert_eq!(
+        Owned::builder().value(11).build().err().unwrap(),
+        OwnedBuilderError::Validation("11 is too large".to_owned())
+    );
+
+    let mut builder = Shape::square_builder();
+    builder.side(0);
+    assert_eq!(
+        builder.build().err().unwrap(),
+        ShapeSquareBuilderError::Validation("empty shape".to_owned())
+    );
+    assert!(matches!(
+        builder.side(2).build().unwrap(),
+        Shape::Square { side: 2 }
+    ));
+
+    let err = Typed::builder().port(0).build().err().unwrap();
+    assert!(matches!(
+        err,
+        TypedBuilderError::InvalidField { field: "port", .. }
+    ));
+}
@@ -0,0 +1,73 @@
+// The `each` attribute works for any collection that implements Default and
+// Extend, not just Vec, and the collection may be named by a full path.
+//
+// For maps the one-at-a-time setter takes the key and the value as two
+// arguments. For collections the macro does not know about, the setter
+// accepts anything the collection can be extended with.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+#[derive(Default)]
+pub struct Counter(usize);
+
+impl<T> Extend<T> for Counter {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0 += iter.into_iter().count();
+    }
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(each = "header")]
+    headers: HashMap<String, String>,
+    #[builder(each = "param")]
+    params: BTreeMap<String, u32>,
+    #[builder(each = "tag")]
+    tags: HashSet<String>,
+    #[builder(each = "flag")]
+    flags: BTreeSet<char>,
+    #[builder(each = "step")]
+    steps: VecDeque<u8>,
+    #[builder(each = "arg")]
+    args: std::vec::Vec<String>,
+    #[builder(each = "count")]
+    counter: Counter,
+}
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Headers {
+    #[builder(each = "header")]
+    headers: std::collections::HashMap<String, String>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .header("Accept".to_owned(), "*/*".to_owned())
+        .header("Host".to_owned(), "localhost".to_owned())
+        .param("page".to_owned(), 2)
+        .tag("a".to_owned())
+        .tag("a".to_owned())
+        .flag('v')
+        .flag('q')
+        .step(1)
+        .step(2)
+        .arg("--release".to_owned())
+        .count(())
+        .count("anything")
+        .build()
+        .unwrap();
+
+    assert_eq!(request.headers.len(), 2);
+    assert_eq!(request.headers["Host"], "localhost");
+    assert_eq!(request.params["page"], 2);
+    assert_eq!(request.tags.len(), 1);
+    assert_eq!(request.flags.iter().collect::<String>(), "qv");
+    assert_eq!(request.steps, [1, 2]);
+    assert_eq!(request.args, ["--release"]);
+    assert_eq!(request.counter.0, 2);
+
+    let headers = Headers::builder().header("Accept", "*/*").build().unwrap();
+    assert_eq!(headers.headers["Accept"], "*/*");
+}
@@ -0,0 +1,43 @@
+// Every malformed field attribute is reported with an error pointing at the
+// offending tokens instead of being silently ignored. All problems found in a
+// derive input are reported together.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(each)]
+    args: Vec<String>,
+    #[builder(each = 3)]
+    env: Vec<String>,
+    #[builder(each = "not an ident")]
+    paths: Vec<String>,
+    #[builder = "x"]
+    executable: String,
+    #[builder]
+    current_dir: Option<String>,
+    #[builder(default(1))]
+    retries: u32,
+    #[builder(setter(into = 1, strip))]
+    user: Option<String>,
+    #[builder(setter)]
+    group: String,
+    #[builder(validate_with = 1 + 1)]
+    timeout: u64,
+    #[builder(sub_builder, default)]
+    parent: Option<Box<Command>>,
+    #[builder(sub_builder)]
+    stdin: (String, String),
+    #[builder(optional)]
+    stdout: String,
+    #[builder(each(append, prepend))]
+    stderr: Vec<String>,
+    #[builder(sub_builder(builder = "CommandBuilder", size = 16))]
+    child: Box<Command>,
+    #[builder(each(name = "input", append))]
+    input: Vec<String>,
+    #[builder(each = "output", try_setter)]
+    output: Vec<String>,
+}
+
+fn main() {}
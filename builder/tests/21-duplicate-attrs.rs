@@ -0,0 +1,24 @@
+// Giving the same option twice is an error, whether the duplicates appear in
+// one attribute or are spread over several. The same goes for options that
+// contradict each other.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+#[builder(pattern = "mutable")]
+pub struct Command {
+    #[builder(each = "arg", each = "argument")]
+    args: Vec<String>,
+    #[builder(default)]
+    #[builder(default = 3)]
+    retries: u32,
+    #[builder(setter(into, into = false))]
+    executable: String,
+    #[builder(vis = "pub", private)]
+    user: String,
+    #[builder(skip, each = "var")]
+    env: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+// Struct-level options are validated as well: unknown options, values of the
+// wrong kind and unknown builder patterns are all reported.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate = true, pattern = "shared", validate = 3)]
+#[builder(each = "arg", setter(unknown), derive(Clone = true))]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
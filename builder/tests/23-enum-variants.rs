@@ -0,0 +1,91 @@
+// Deriving Builder on an enum generates one builder per variant with named
+// fields. The builder for variant `Request` of enum `Message` is named
+// `MessageRequestBuilder`, is created with `Message::request_builder()` and its
+// build() method returns a `Message`. Variants without named fields get no
+// builder.
+//
+// Every field attribute works the same way as on a struct. Options on the enum
+// apply to the builders of all variants, and a variant may add its own or
+// override those of the enum. Builders are named after their variants, so
+// `name` and `constructor` can only be given on a variant.
+//
+// A run of capitals in a variant name is one word: the builder of variant
+// `HTTPRequest` is created with `Message::http_request_builder()`.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub enum Message<T> {
+    Request {
+        id: u64,
+        #[builder(each = "header")]
+        headers: Vec<String>,
+        body: Option<T>,
+    },
+    #[builder(setter(into))]
+    GetUser {
+        name: String,
+        #[builder(default = 30)]
+        timeout: u32,
+    },
+    #[builder(pattern = "mutable", constructor = "new_http_request")]
+    HTTPRequest {
+        url: String,
+    },
+    HTTPSRedirect {
+        location: String,
+    },
+    Ping(u8),
+    Close,
+}
+
+fn main() {
+    let request = Message::request_builder()
+        .id(7)
+        .header("Accept".to_owned())
+        .body("payload")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        Message::Request {
+            id: 7,
+            headers: vec!["Accept".to_owned()],
+            body: Some("payload"),
+        }
+    );
+
+    let get_user = Message::<()>::get_user_builder()
+        .name("ferris")
+        .build()
+        .unwrap();
+    assert_eq!(
+        get_user,
+        Message::GetUser {
+            name: "ferris".to_owned(),
+            timeout: 30,
+        }
+    );
+
+    let mut builder = Message::<()>::new_http_request();
+    builder.url("/".to_owned());
+    assert_eq!(
+        builder.build().unwrap(),
+        Message::HTTPRequest {
+            url: "/".to_owned()
+        }
+    );
+
+    let redirect = Message::<()>::https_redirect_builder()
+        .location("/login".to_owned())
+        .build()
+        .unwrap();
+    assert!(matches!(redirect, Message::HTTPSRedirect { .. }));
+
+    let err = Message::<()>::request_builder().build().err().unwrap();
+    assert_eq!(
+        err,
+        MessageRequestBuilderError::MissingFields(vec!["id".to_owned()])
+    );
+}
@@ -0,0 +1,42 @@
+// Tuple structs get positional builders. The setter for each field is named
+// after its position, `_0`, `_1` and so on, unless the field is given a name
+// with #[builder(name = "...")]. Field attributes work as on named fields.
+//
+// Unit structs get a builder without setters.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct UserId(u64);
+
+#[derive(Builder)]
+pub struct Endpoint(
+    #[builder(name = "host", setter(into))] String,
+    #[builder(default = 80)] u16,
+    Option<String>,
+);
+
+#[derive(Builder)]
+pub struct Marker;
+
+fn main() {
+    let id = UserId::builder()._0(42).build().unwrap();
+    assert_eq!(id.0, 42);
+
+    let endpoint = Endpoint::builder()
+        .host("localhost")
+        ._2("/health".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(endpoint.0, "localhost");
+    assert_eq!(endpoint.1, 80);
+    assert_eq!(endpoint.2.as_deref(), Some("/health"));
+
+    let err = Endpoint::builder()._1(8080).build().err().unwrap();
+    assert_eq!(
+        err,
+        EndpointBuilderError::MissingFields(vec!["host".to_owned()])
+    );
+
+    let Marker = Marker::builder().build().unwrap();
+}
@@ -0,0 +1,38 @@
+// The builder, its setters, build() and the builder() constructor take the
+// visibility of the struct they are derived for, so a public struct in a
+// module can be built from outside of it.
+//
+// #[builder(vis = "...")] on the struct changes the visibility of everything
+// that is generated; on a field it only changes that field's setters.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        pub port: u16,
+    }
+
+    #[derive(Builder)]
+    #[builder(vis = "pub(crate)")]
+    pub struct Client {
+        pub retries: u32,
+        #[builder(vis = "pub(super)")]
+        pub timeout: Option<u64>,
+    }
+}
+
+use config::{Client, ClientBuilder, Server, ServerBuilder};
+
+fn main() {
+    let mut builder: ServerBuilder = Server::builder();
+    builder.host("localhost".to_owned()).port(8080);
+    let server = builder.build().unwrap();
+    assert_eq!(server.port, 8080);
+
+    let mut builder: ClientBuilder = Client::builder();
+    let client = builder.retries(3).timeout(10).build().unwrap();
+    assert_eq!(client.retries, 3);
+    assert_eq!(client.timeout, Some(10));
+}
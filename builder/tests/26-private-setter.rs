@@ -0,0 +1,26 @@
+// A field annotated with #[builder(private)] keeps its setter private to the
+// module of the struct, for example so that it can only be set by a custom
+// constructor.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        #[builder(private)]
+        pub id: u64,
+    }
+
+    impl ServerBuilder {
+        pub fn with_generated_id(&mut self) -> &mut Self {
+            self.id(42)
+        }
+    }
+}
+
+fn main() {
+    let mut builder = config::Server::builder();
+    builder.with_generated_id();
+    builder.id(7);
+}
@@ -0,0 +1,76 @@
+// The names of the generated API can be chosen on the struct:
+//
+//   - #[builder(name = "...")] names the builder; the generated error type is
+//     named after it with an `Error` suffix.
+//   - #[builder(constructor = "...")] names the method creating the builder.
+//   - #[builder(build_fn(name = "...", error = "..."))] names the build method
+//     and makes it return a custom error type, which must implement From for
+//     the generated error type.
+
+use derive_builder::Builder;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Builder(String),
+}
+
+impl From<ConnectionOptionsError> for ConfigError {
+    fn from(err: ConnectionOptionsError) -> Self {
+        ConfigError::Builder(err.to_string())
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Builder(message) => f.write_str(message),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(
+    name = "ConnectionOptions",
+    constructor = "options",
+    build_fn(name = "connect", error = "ConfigError")
+)]
+pub struct Connection {
+    host: String,
+    port: u16,
+}
+
+// Hand-written methods named like the defaults no longer collide.
+impl Connection {
+    pub fn builder() -> &'static str {
+        "hand-written"
+    }
+}
+
+#[derive(Builder)]
+#[builder(build_fn(error = "Box<dyn std::error::Error>"))]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {
+    let mut options: ConnectionOptions = Connection::options();
+    let connection = options
+        .host("localhost".to_owned())
+        .port(5432)
+        .connect()
+        .unwrap();
+    assert_eq!(connection.host, "localhost");
+    assert_eq!(connection.port, 5432);
+    assert_eq!(Connection::builder(), "hand-written");
+
+    let err = Connection::options().port(5432).connect().err().unwrap();
+    assert_eq!(
+        err,
+        ConfigError::Builder("field `host` was not set".to_owned())
+    );
+    let _: ConnectionOptionsError = ConnectionOptionsError::MissingFields(vec!["host".to_owned()]);
+
+    let err = Command::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "field `executable` was not set");
+}
@@ -0,0 +1,80 @@
+// Traits can be derived for the builder with #[builder(derive(...))], and any
+// other attribute can be forwarded onto it with #[builder(attr(...))]. Every
+// builder implements Default, starting out with no field set, so it can be
+// embedded in other structs; deriving Default as well changes nothing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(derive(Clone, Debug, PartialEq), attr(allow(dead_code)))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+// Derives on a typestate builder hold in every state.
+#[derive(Builder)]
+#[builder(typestate, derive(Clone, Debug, Default))]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+// The immutable pattern needs Clone, which may be derived explicitly as well.
+#[derive(Builder)]
+#[builder(
+    pattern = "immutable",
+    derive(std::clone::Clone, Debug, core::default::Default)
+)]
+pub struct Size {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Default)]
+pub struct Job {
+    command: CommandBuilder,
+    retries: u32,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder
+        .executable("cargo".to_owned())
+        .arg("build".to_owned());
+
+    let mut snapshot = builder.clone();
+    builder.arg("--release".to_owned());
+    assert_ne!(snapshot, builder);
+
+    let debug = format!("{:?}", snapshot);
+    assert!(debug.starts_with("CommandBuilder"));
+    assert!(debug.contains("\"cargo\""));
+
+    let command = snapshot.build().unwrap();
+    assert_eq!(command.args, vec!["build"]);
+    let command = builder.build().unwrap();
+    assert_eq!(command.args, vec!["build", "--release"]);
+
+    assert_eq!(CommandBuilder::default(), Command::builder());
+
+    let x = Point::builder().x(1);
+    let point = x.clone().y(2).build().unwrap();
+    assert_eq!((point.x, point.y), (1, 2));
+    assert!(format!("{:?}", x).starts_with("PointBuilder"));
+    let point = PointBuilder::default().x(3).y(4).build().unwrap();
+    assert_eq!((point.x, point.y), (3, 4));
+
+    let size = Size::builder().width(3).height(4).build().unwrap();
+    assert_eq!((size.width, size.height), (3, 4));
+
+    let mut job = Job::default();
+    job.command.executable("make".to_owned());
+    job.retries = 3;
+    let command = job.command.build().unwrap();
+    assert_eq!(command.executable, "make");
+    assert!(command.current_dir.is_none());
+    assert_eq!(job.retries, 3);
+}
@@ -0,0 +1,86 @@
+// An existing value can be turned back into a builder, either by moving it
+// with From or by cloning its fields with to_builder(). Every slot of the
+// builder is populated, including collections used with `each`, so a single
+// field can be changed before building a new value.
+//
+// to_builder() is only available when every field is Clone, but a struct with
+// other fields still gets the From conversion.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = 3)]
+    retries: u32,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+#[derive(Builder)]
+pub struct Rgb(u8, u8, u8);
+
+pub struct Handle;
+
+#[derive(Builder)]
+pub struct Connection {
+    handle: Handle,
+    name: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    let release = command
+        .to_builder()
+        .arg("--release".to_owned())
+        .retries(5)
+        .build()
+        .unwrap();
+    assert_eq!(release.executable, "cargo");
+    assert_eq!(release.args, vec!["build", "--release"]);
+    assert_eq!(release.current_dir.as_deref(), Some(".."));
+    assert_eq!(release.retries, 5);
+
+    let rebuilt = CommandBuilder::from(release).build().unwrap();
+    assert_eq!(rebuilt.args, vec!["build", "--release"]);
+    assert_eq!(rebuilt.retries, 5);
+
+    let unchanged = rebuilt.to_builder().build().unwrap();
+    assert_eq!(unchanged, rebuilt);
+
+    // Typestate builders come back with every required field set.
+    let pair = Pair::builder().first(1).second(2).build().unwrap();
+    let pair = pair.to_builder().second(3).build().unwrap();
+    assert_eq!((pair.first, pair.second), (1, 3));
+    let pair = PairBuilder::from(pair).first(0).build().unwrap();
+    assert_eq!((pair.first, pair.second), (0, 3));
+
+    let rgb = Rgb::builder()._0(1)._1(2)._2(3).build().unwrap();
+    let rgb = rgb.to_builder()._2(4).build().unwrap();
+    assert_eq!((rgb.0, rgb.1, rgb.2), (1, 2, 4));
+
+    let connection = Connection::builder()
+        .handle(Handle)
+        .name("db".to_owned())
+        .build()
+        .unwrap();
+    let connection = ConnectionBuilder::from(connection)
+        .name("cache".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(connection.name, "cache");
+}
@@ -0,0 +1,170 @@
+// A field whose type also derives Builder can be marked with
+// #[builder(sub_builder)]. Its accessor then returns the nested builder,
+// creating it on first use, and build() builds the nested value too.
+//
+// Errors of the nested builder are reported with the path of the field, e.g.
+// "field `listener.address.port` was not set". An Option field is only built
+// if its nested builder was used.
+//
+// When a nested builder fails to build, a mutable outer builder keeps the
+// values of all its nested builders, so that it can be fixed and built again.
+//
+// The nested builder is assumed to be named after the type, e.g.
+// `AddressBuilder`, and to be built with build(). For a type whose builder was
+// renamed, both can be given with #[builder(sub_builder(builder = "...",
+// build_fn = "..."))].
+//
+// The nested builder has to be modified in place and fail with its generated
+// error type, so it must use the default mutable pattern and cannot be given a
+// build_fn(error = "...") or be a typestate builder.
+
+use derive_builder::Builder;
+
+fn nonzero(port: &u16) -> Result<(), &'static str> {
+    if *port == 0 {
+        Err("port must not be 0")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Address {
+    host: String,
+    #[builder(validate_with = nonzero)]
+    port: u16,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Listener {
+    #[builder(sub_builder)]
+    address: Address,
+    #[builder(default = 128)]
+    backlog: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(name = "TlsOptions", build_fn(name = "finish"))]
+pub struct Tls {
+    certificate: String,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Server {
+    name: String,
+    #[builder(sub_builder)]
+    listener: Listener,
+    #[builder(sub_builder)]
+    admin: Option<Address>,
+    #[builder(sub_builder(builder = "TlsOptions", build_fn = "finish"))]
+    tls: Option<Tls>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(validate = "distinct")]
+pub struct Route {
+    #[builder(sub_builder)]
+    from: Address,
+    #[builder(sub_builder)]
+    to: Address,
+}
+
+fn distinct(route: &Route) -> Result<(), &'static str> {
+    if route.from == route.to {
+        Err("route leads nowhere")
+    } else {
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut builder = Server::builder().name("api".to_owned());
+    builder
+        .listener()
+        .backlog(64)
+        .address()
+        .host("0.0.0.0".to_owned())
+        .port(8080);
+    let server = builder.build().unwrap();
+    assert_eq!(server.name, "api");
+    assert_eq!(server.listener.backlog, 64);
+    assert_eq!(
+        server.listener.address,
+        Address {
+            host: "0.0.0.0".to_owned(),
+            port: 8080,
+        }
+    );
+    assert_eq!(server.admin, None);
+
+    let mut builder = Server::builder().name("api".to_owned());
+    builder.listener().address().host("0.0.0.0".to_owned());
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.to_string(), "field `listener.address.port` was not set");
+    assert_eq!(
+        err,
+        ServerBuilderError::MissingFields(vec!["listener.address.port".to_owned()])
+    );
+
+    // An untouched nested builder is missing its own fields.
+    let err = Listener::builder().build().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "fields `address.host`, `address.port` were not set"
+    );
+
+    let mut builder = Server::builder().name("api".to_owned());
+    builder
+        .listener()
+        .address()
+        .host("0.0.0.0".to_owned())
+        .port(8080);
+    builder.admin().host("127.0.0.1".to_owned()).port(9090);
+    let server = builder.build().unwrap();
+    assert_eq!(server.admin.unwrap().port, 9090);
+
+    let mut builder = Server::builder().name("api".to_owned());
+    builder
+        .listener()
+        .address()
+        .host("0.0.0.0".to_owned())
+        .port(443);
+    builder.tls();
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.to_string(), "field `tls.certificate` was not set");
+
+    let mut builder = Server::builder().name("api".to_owned());
+    builder
+        .listener()
+        .address()
+        .host("0.0.0.0".to_owned())
+        .port(443);
+    builder.tls().certificate("cert.pem".to_owned());
+    let server = builder.build().unwrap();
+    assert_eq!(server.tls.unwrap().certificate, "cert.pem");
+
+    let mut builder = Route::builder();
+    builder.from().host("a".to_owned()).port(80);
+    builder.to().host("b".to_owned()).port(0);
+    let err = builder.build().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for field `to.port`: port must not be 0"
+    );
+    builder.to().port(80);
+    let route = builder.build().unwrap();
+    assert_eq!((route.from.host.as_str(), route.to.port), ("a", 80));
+
+    let mut builder = Route::builder();
+    builder.from().host("a".to_owned()).port(80);
+    builder.to().host("a".to_owned()).port(80);
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.to_string(), "validation failed: route leads nowhere");
+    builder.to().host("b".to_owned());
+    let route = builder.build().unwrap();
+    assert_eq!(
+        (route.from.host.as_str(), route.to.host.as_str()),
+        ("a", "b")
+    );
+}
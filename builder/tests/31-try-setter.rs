@@ -0,0 +1,75 @@
+// With #[builder(try_setter)] a field also gets a `try_` setter accepting any
+// value that converts into the field's type with TryInto. The conversion error
+// is returned instead of the builder, which is left unchanged. On the struct,
+// the option applies to every field.
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+use std::num::TryFromIntError;
+
+pub struct Port(u16);
+
+impl TryFrom<&str> for Port {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value
+            .parse()
+            .map(Port)
+            .map_err(|_| format!("invalid port `{}`", value))
+    }
+}
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(try_setter)]
+    port: Port,
+    #[builder(try_setter, setter(into))]
+    workers: u8,
+    #[builder(try_setter)]
+    timeout: Option<u32>,
+    name: String,
+}
+
+#[derive(Builder)]
+#[builder(typestate, try_setter)]
+pub struct Limits {
+    connections: u16,
+    #[builder(default)]
+    requests: u32,
+}
+
+fn main() -> Result<(), TryFromIntError> {
+    let mut builder = Server::builder();
+    builder
+        .try_port("8080")
+        .unwrap()
+        .try_workers(4u64)?
+        .try_timeout(30i64)?
+        .name("api".to_owned());
+
+    let err = builder.try_workers(1000u32).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "out of range integral type conversion attempted"
+    );
+    let err = builder.try_port("http").err().unwrap();
+    assert_eq!(err, "invalid port `http`");
+
+    let server = builder.build().unwrap();
+    assert_eq!(server.port.0, 8080);
+    assert_eq!(server.workers, 4);
+    assert_eq!(server.timeout, Some(30));
+    assert_eq!(server.name, "api");
+
+    let limits = Limits::builder()
+        .try_connections(100u64)?
+        .try_requests(5000i32)?
+        .build()
+        .unwrap();
+    assert_eq!(limits.connections, 100);
+    assert_eq!(limits.requests, 5000);
+    assert!(Limits::builder().try_connections(-1).is_err());
+
+    Ok(())
+}
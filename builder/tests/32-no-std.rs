@@ -0,0 +1,94 @@
+// With #[builder(no_std)] the generated code only refers to `core` and
+// `alloc`, so it can be used in crates without std. The crate has to declare
+// `extern crate alloc`. The generated error type does not implement
+// std::error::Error in this mode.
+//
+// std is only linked here to provide the runtime for main, under a different
+// name so that none of the generated code can refer to it.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as host;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+fn nonzero(value: &u32) -> Result<(), &'static str> {
+    if *value == 0 {
+        Err("must not be zero")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder)]
+#[builder(no_std)]
+pub struct Pin {
+    number: u8,
+    label: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(no_std, derive(Clone, Debug))]
+pub struct Uart {
+    #[builder(validate_with = nonzero)]
+    baud_rate: u32,
+    #[builder(each = "pin")]
+    pins: Vec<u8>,
+    #[builder(default = 8)]
+    data_bits: u8,
+}
+
+#[derive(Builder)]
+#[builder(no_std)]
+pub struct Board {
+    name: String,
+    #[builder(sub_builder)]
+    status_pin: Pin,
+}
+
+#[derive(Builder)]
+#[builder(no_std, typestate)]
+pub struct Timer {
+    prescaler: u16,
+    period: u16,
+}
+
+fn main() {
+    let uart = Uart::builder()
+        .baud_rate(115_200)
+        .pin(1)
+        .pin(2)
+        .build()
+        .unwrap();
+    assert_eq!(uart.baud_rate, 115_200);
+    assert_eq!(uart.pins, [1, 2]);
+    assert_eq!(uart.data_bits, 8);
+    let uart = uart.to_builder().data_bits(7).build().unwrap();
+    assert_eq!(uart.data_bits, 7);
+
+    let err = Uart::builder().baud_rate(0).build().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for field `baud_rate`: must not be zero"
+    );
+
+    let mut builder = Board::builder();
+    builder.name("devkit".to_string()).status_pin().number(13);
+    let board = builder.build().unwrap();
+    assert_eq!(board.name, "devkit");
+    assert_eq!(board.status_pin.number, 13);
+    assert_eq!(board.status_pin.label, None);
+
+    let err = Board::builder()
+        .name("devkit".to_string())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "field `status_pin.number` was not set");
+
+    let timer = Timer::builder().prescaler(8).period(1000).build().unwrap();
+    assert_eq!((timer.prescaler, timer.period), (8, 1000));
+}
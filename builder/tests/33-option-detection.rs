@@ -0,0 +1,100 @@
+// Does your macro still recognize Option and the standard collections when
+// they are written with their full path, and leave alone types that only
+// share their name?
+//
+// A field is optional when its type is Option<T>, std::option::Option<T> or
+// ::core::option::Option<T>, but not when it is some other module's Option.
+// Similarly `each` only knows the item type of the standard collections; any
+// other collection gets a generic setter relying on its Extend impl.
+//
+// The macro cannot see through type aliases, so an alias of Option has to be
+// marked with #[builder(optional)]. Nor can it see imports, so a field whose
+// type is another Option imported under that name has to be marked with
+// #[builder(optional = false)] to be required like any other field.
+
+use derive_builder::Builder;
+
+mod custom {
+    #[derive(Debug, PartialEq)]
+    pub struct Option<T>(pub T);
+
+    #[derive(Default)]
+    pub struct Vec<T>(pub std::vec::Vec<T>);
+
+    impl<T> Extend<T> for Vec<T> {
+        fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+            self.0.extend(iter);
+        }
+    }
+}
+
+type Maybe<T> = std::option::Option<T>;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    current_dir: std::option::Option<String>,
+    timeout: ::core::option::Option<u64>,
+    user: custom::Option<String>,
+    #[builder(optional)]
+    group: Maybe<String>,
+    #[builder(each = "arg")]
+    args: std::vec::Vec<String>,
+    #[builder(each = "env")]
+    env: ::std::collections::BTreeMap<String, String>,
+    #[builder(each = "path")]
+    paths: custom::Vec<String>,
+}
+
+mod flags {
+    use super::custom::Option;
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Flag {
+        // The custom Option, which the macro would take for the std one.
+        #[builder(optional = false)]
+        pub value: Option<bool>,
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .timeout(30)
+        .user(custom::Option("root".to_owned()))
+        .group("wheel".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
+        .path("/usr/bin".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout, Some(30));
+    assert_eq!(command.user, custom::Option("root".to_owned()));
+    assert_eq!(command.group.as_deref(), Some("wheel"));
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.paths.0, vec!["/usr/bin"]);
+
+    // A field of some other Option type is required.
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec!["user".to_owned()])
+    );
+
+    let err = flags::Flag::builder().build().err().unwrap();
+    assert_eq!(
+        err,
+        flags::FlagBuilderError::MissingFields(vec!["value".to_owned()])
+    );
+    let mut builder = flags::Flag::builder();
+    let flag = builder.value(custom::Option(true)).build().unwrap();
+    assert_eq!(flag.value, custom::Option(true));
+}
@@ -0,0 +1,89 @@
+//! The builder and everything generated for it is documented, so the macro
+//! can be used in crates that deny missing docs.
+//!
+//! Field docs are copied onto the field's setters, including `each` and `try_`
+//! setters, and the builder is documented as the builder of its target. Both
+//! can be replaced with #[builder(doc = "...")]. The other generated methods,
+//! `extend_`, `clear_`, `reset_` and `unset_`, describe what they do in docs of
+//! their own.
+
+#![deny(missing_docs)]
+
+use derive_builder::Builder;
+
+/// A command to run.
+#[derive(Builder)]
+#[builder(try_setter)]
+pub struct Command {
+    /// The program to run.
+    pub executable: String,
+    /// Arguments passed to the program.
+    #[builder(each = "arg")]
+    pub args: Vec<String>,
+    /// The directory to run in, defaulting to the current one.
+    #[builder(doc = "Sets the directory the program runs in.")]
+    pub current_dir: Option<String>,
+    /// Connection details.
+    #[builder(sub_builder)]
+    pub address: Address,
+}
+
+/// Where to connect to.
+#[derive(Builder)]
+#[builder(doc = "Collects the parts of an [`Address`].")]
+pub struct Address {
+    /// The host name.
+    pub host: String,
+    /// The port number.
+    pub port: u16,
+}
+
+/// A point.
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Point {
+    /// The horizontal coordinate.
+    pub x: i32,
+    /// The vertical coordinate.
+    pub y: i32,
+}
+
+/// A message.
+#[derive(Builder)]
+pub enum Message {
+    /// A request.
+    Request {
+        /// The path requested.
+        path: String,
+    },
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .try_current_dir("..")
+        .unwrap();
+    let err = builder.build().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "fields `address.host`, `address.port` were not set"
+    );
+
+    let address = Address::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .build()
+        .unwrap();
+    assert_eq!(address.port, 80);
+
+    let point = Point::builder().x(1).y(2).build().unwrap();
+    assert_eq!((point.x, point.y), (1, 2));
+
+    let message = Message::request_builder()
+        .path("/".to_owned())
+        .build()
+        .unwrap();
+    assert!(matches!(message, Message::Request { .. }));
+}
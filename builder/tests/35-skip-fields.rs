@@ -0,0 +1,95 @@
+// Fields marked with #[builder(skip)] get no slot and no setter in the
+// builder. build() initializes them with Default, or with the expression given
+// by `default = ...`.
+//
+// With #[builder(setter(custom))] the slot of the field is generated as usual
+// but its setters are left to be written by hand, e.g. to accept the value in
+// a different form. The field is still required unless it has a default.
+//
+// Generic parameters used only by skipped fields are still parameters of the
+// builder.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Document {
+    title: String,
+    #[builder(setter(custom))]
+    words: Vec<String>,
+    #[builder(skip)]
+    cache: HashMap<String, usize>,
+    #[builder(skip, default = next_id())]
+    id: u64,
+}
+
+fn next_id() -> u64 {
+    42
+}
+
+impl DocumentBuilder {
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        self.words = Some(text.split_whitespace().map(str::to_owned).collect());
+        self
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Counter {
+    name: String,
+    #[builder(skip)]
+    count: usize,
+}
+
+#[derive(Builder)]
+pub struct Cache<T: Default> {
+    #[builder(skip)]
+    entries: Vec<T>,
+    capacity: usize,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Pool<T> {
+    #[builder(skip)]
+    items: Vec<T>,
+    size: usize,
+}
+
+fn main() {
+    let document = Document::builder()
+        .title("Notes".to_owned())
+        .text("one two  three")
+        .build()
+        .unwrap();
+    assert_eq!(document.title, "Notes");
+    assert_eq!(document.words, vec!["one", "two", "three"]);
+    assert!(document.cache.is_empty());
+    assert_eq!(document.id, 42);
+
+    // A field with a custom setter is still required.
+    let err = Document::builder()
+        .title("Notes".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        DocumentBuilderError::MissingFields(vec!["words".to_owned()])
+    );
+
+    let counter = Counter::builder().name("hits".to_owned()).build().unwrap();
+    assert_eq!(counter.name, "hits");
+    assert_eq!(counter.count, 0);
+
+    let cache: Cache<String> = Cache::builder().capacity(8).build().unwrap();
+    assert!(cache.entries.is_empty());
+    let cache = cache.to_builder().capacity(16).build().unwrap();
+    assert_eq!(cache.capacity, 16);
+
+    let pool: Pool<u8> = Pool::builder().size(4).build().unwrap();
+    assert!(pool.items.is_empty());
+    let pool = PoolBuilder::from(pool).size(2).build().unwrap();
+    assert_eq!(pool.size, 2);
+}
@@ -0,0 +1,20 @@
+// A skipped field has no setter at all, and neither has a field whose setter
+// is marked as custom until one is written.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Document {
+    title: String,
+    #[builder(skip)]
+    id: u64,
+    #[builder(setter(custom))]
+    words: Vec<String>,
+}
+
+fn main() {
+    let mut builder = Document::builder();
+    builder.title("Notes".to_owned());
+    builder.id(7);
+    builder.words(Vec::new());
+}
@@ -0,0 +1,78 @@
+// With #[builder(merge)] builders can be combined with merge(), e.g. to layer
+// partial configurations from several sources. Every field set in the merged
+// builder overwrites the same field of the builder it is merged into, while
+// fields it leaves unset keep their value. Nested builders of `sub_builder`
+// fields are merged field by field, so they have to be mergeable too.
+//
+// Without the option no merge() is generated and a field may be named `merge`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Config {
+    name: String,
+    #[builder(default = 4)]
+    workers: u32,
+    #[builder(each = "feature")]
+    features: Vec<String>,
+    log_file: Option<String>,
+    #[builder(sub_builder)]
+    database: Database,
+}
+
+#[derive(Builder)]
+pub struct Strategy {
+    merge: bool,
+    name: String,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults
+        .name("app".to_owned())
+        .workers(8)
+        .feature("metrics".to_owned())
+        .database()
+        .host("localhost".to_owned())
+        .port(5432);
+
+    let mut overrides = Config::builder();
+    overrides
+        .workers(16)
+        .log_file("app.log".to_owned())
+        .database()
+        .host("db.internal".to_owned());
+
+    let config = defaults.merge(overrides).build().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.workers, 16);
+    assert_eq!(config.features, vec!["metrics"]);
+    assert_eq!(config.log_file.as_deref(), Some("app.log"));
+    assert_eq!(config.database.host, "db.internal");
+    assert_eq!(config.database.port, 5432);
+
+    // Required fields missing from every layer are still reported.
+    let mut partial = Config::builder();
+    partial.merge(Config::builder()).database().port(5432);
+    let err = partial.build().err().unwrap();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields(vec!["name".to_owned(), "database.host".to_owned()])
+    );
+
+    let strategy = Strategy::builder()
+        .merge(true)
+        .name("recursive".to_owned())
+        .build()
+        .unwrap();
+    assert!(strategy.merge);
+    assert_eq!(strategy.name, "recursive");
+}
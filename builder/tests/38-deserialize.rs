@@ -0,0 +1,71 @@
+// With the `serde` feature of derive_builder, #[builder(deserialize)] makes
+// the builder implement serde::Deserialize with every field optional, so
+// partial values can be loaded from several sources, merged, and built once
+// complete. Fields are named like in the target struct, even when their setter
+// is renamed. The builders of nested values have to be deserializable too.
+//
+// A missing Option<T> field is left unset, while `null` sets it to None
+// explicitly so that it overrides a value when merged.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(deserialize)]
+pub struct Database {
+    host: String,
+    #[builder(default = 5432)]
+    port: u16,
+}
+
+#[derive(Builder, Debug)]
+#[builder(deserialize)]
+pub struct Config {
+    name: String,
+    #[builder(name = "worker_count")]
+    workers: u32,
+    #[builder(each = "feature")]
+    features: Vec<String>,
+    log_file: Option<String>,
+    #[builder(sub_builder)]
+    database: Database,
+}
+
+#[derive(Builder, Debug)]
+#[builder(deserialize)]
+pub enum Source {
+    File { path: String },
+}
+
+fn main() {
+    let mut builder: ConfigBuilder = serde_json::from_str(
+        r#"{
+            "name": "app",
+            "features": ["metrics"],
+            "log_file": "app.log",
+            "database": { "host": "localhost" }
+        }"#,
+    )
+    .unwrap();
+
+    let err = builder.build().err().unwrap();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields(vec!["workers".to_owned()])
+    );
+
+    let overrides: ConfigBuilder =
+        serde_json::from_str(r#"{ "workers": 8, "log_file": null, "database": { "port": 6543 } }"#)
+            .unwrap();
+    let config = builder.merge(overrides).build().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.workers, 8);
+    assert_eq!(config.features, vec!["metrics"]);
+    assert_eq!(config.log_file, None);
+    assert_eq!(config.database.host, "localhost");
+    assert_eq!(config.database.port, 6543);
+
+    let mut source: SourceFileBuilder =
+        serde_json::from_str(r#"{ "path": "config.json" }"#).unwrap();
+    let Source::File { path } = source.build().unwrap();
+    assert_eq!(path, "config.json");
+}
@@ -0,0 +1,76 @@
+// build() checks every required field before failing, so a single error lists
+// all of the fields that are still missing, in the order they are declared.
+// Fields that have been set, have a default or are optional are not listed.
+// Missing fields of nested builders are listed in the same error by their
+// path, even when fields of the outer builder are missing too.
+//
+// Fields are named as in the struct, even when their setter is renamed.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    name: String,
+    host: String,
+    #[builder(default = 8080)]
+    port: u16,
+    user: String,
+    password: Option<String>,
+    #[builder(name = "timeout_secs")]
+    timeout: u64,
+}
+
+#[derive(Builder)]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder, name = "settings")]
+    config: Config,
+}
+
+fn main() {
+    let err = Config::builder().build().err().unwrap();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields(vec![
+            "name".to_owned(),
+            "host".to_owned(),
+            "user".to_owned(),
+            "timeout".to_owned()
+        ])
+    );
+    assert_eq!(
+        err.to_string(),
+        "fields `name`, `host`, `user`, `timeout` were not set"
+    );
+
+    let err = Config::builder()
+        .host("localhost".to_owned())
+        .timeout_secs(30)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields(vec!["name".to_owned(), "user".to_owned()])
+    );
+
+    let err = Service::builder().build().err().unwrap();
+    assert_eq!(
+        err,
+        ServiceBuilderError::MissingFields(vec![
+            "name".to_owned(),
+            "config.name".to_owned(),
+            "config.host".to_owned(),
+            "config.user".to_owned(),
+            "config.timeout".to_owned(),
+        ])
+    );
+
+    let mut builder = Service::builder();
+    builder.settings().name("api".to_owned());
+    let err = builder.build().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "fields `name`, `config.host`, `config.user`, `config.timeout` were not set"
+    );
+}
@@ -0,0 +1,83 @@
+// Besides the setter adding one item, a field with `each` gets extend_<field>
+// adding any number of items and clear_<field> emptying the collection.
+//
+// Written as #[builder(each(name = "...", append))], the setter taking the
+// whole collection appends to it instead of replacing what was added before.
+// For an Option field whose setter takes the Option itself, appending None
+// leaves the field as it was.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Default)]
+pub struct Words(Vec<String>);
+
+impl Extend<String> for Words {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env", setter(into))]
+    env: BTreeMap<String, String>,
+    #[builder(each(name = "flag", append))]
+    flags: BTreeSet<char>,
+    #[builder(each = "word")]
+    words: Words,
+}
+
+#[derive(Builder)]
+pub struct Job {
+    #[builder(each(name = "tag", append), setter(strip_option = false))]
+    tags: Option<Vec<String>>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .extend_args(vec!["--release".to_owned(), "--locked".to_owned()])
+        .extend_env([("RUST_LOG", "debug"), ("RUST_BACKTRACE", "1")])
+        .env("CARGO_TERM_COLOR", "always")
+        .flag('v')
+        .flags(['q', 'x'].into_iter().collect())
+        .flag('z')
+        .extend_words(vec!["a".to_owned(), "b".to_owned()])
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, vec!["build", "--release", "--locked"]);
+    assert_eq!(command.env.len(), 3);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.flags.into_iter().collect::<String>(), "qvxz");
+    assert_eq!(command.words.0, vec!["a", "b"]);
+
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .env("RUST_LOG", "debug")
+        .clear_args()
+        .clear_env()
+        .extend_args(Some("test".to_owned()))
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["test"]);
+    assert!(command.env.is_empty());
+
+    let job = Job::builder()
+        .tags(Some(vec!["a".to_owned()]))
+        .tag("b".to_owned())
+        .tags(None)
+        .tags(Some(vec!["c".to_owned()]))
+        .build()
+        .unwrap();
+    assert_eq!(
+        job.tags,
+        Some(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()])
+    );
+
+    let job = Job::builder().tags(None).build().unwrap();
+    assert_eq!(job.tags, None);
+}
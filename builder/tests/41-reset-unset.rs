@@ -0,0 +1,154 @@
+// Every field gets a `reset_` method that returns it to not being set, as if
+// its setter had never been called. On a typestate builder, resetting a
+// required field moves it back to the unset state.
+//
+// The builder keeps an Option<T> field that was never set apart from one set
+// to None: `unset_` sets it to None explicitly, as does passing None to a
+// setter with #[builder(setter(strip_option = false))]. An explicit None
+// overrides the field's default and wins when merged into another builder, so
+// a builder can describe a partial update that clears some fields.
+//
+// A struct's `validate` function is given the built value, so it sees the
+// default of a field that was never set and None for one set to None.
+
+use derive_builder::Builder;
+
+fn nonzero(value: &u32) -> Result<(), &'static str> {
+    if *value == 0 {
+        Err("must not be 0")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug, Clone)]
+#[builder(merge)]
+pub struct User {
+    name: String,
+    email: Option<String>,
+    #[builder(default = Some("UTC".to_owned()))]
+    timezone: Option<String>,
+    #[builder(setter(strip_option = false))]
+    nickname: Option<String>,
+    #[builder(validate_with = nonzero)]
+    quota: Option<u32>,
+    #[builder(sub_builder)]
+    address: Address,
+}
+
+#[derive(Builder, Debug, Clone, Default)]
+#[builder(merge)]
+pub struct Address {
+    #[builder(default)]
+    city: String,
+}
+
+#[derive(Builder, Debug)]
+#[builder(validate = "known_timezone")]
+pub struct Profile {
+    #[builder(default = Some("UTC".to_owned()))]
+    timezone: Option<String>,
+}
+
+fn known_timezone(profile: &Profile) -> Result<(), String> {
+    match profile.timezone.as_deref() {
+        Some("UTC") | Some("CET") | None => Ok(()),
+        Some(other) => Err(format!("unknown timezone {}", other)),
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Point {
+    x: i32,
+    y: Option<i32>,
+}
+
+fn main() {
+    let err = User::builder()
+        .name("alice".to_owned())
+        .reset_name()
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        UserBuilderError::MissingFields(vec!["name".to_owned()])
+    );
+
+    // A field that was never set builds its default, an explicit None does not.
+    let user = User::builder().name("alice".to_owned()).build().unwrap();
+    assert_eq!(user.timezone.as_deref(), Some("UTC"));
+    let user = User::builder()
+        .name("alice".to_owned())
+        .timezone("CET".to_owned())
+        .unset_timezone()
+        .build()
+        .unwrap();
+    assert_eq!(user.timezone, None);
+    let user = User::builder()
+        .name("alice".to_owned())
+        .unset_timezone()
+        .reset_timezone()
+        .build()
+        .unwrap();
+    assert_eq!(user.timezone.as_deref(), Some("UTC"));
+
+    // A validator is only called with a value.
+    let user = User::builder()
+        .name("alice".to_owned())
+        .quota(0)
+        .unset_quota()
+        .build()
+        .unwrap();
+    assert_eq!(user.quota, None);
+
+    let mut builder = User::builder();
+    builder.address().city("Berlin".to_owned());
+    let user = builder
+        .name("alice".to_owned())
+        .reset_address()
+        .build()
+        .unwrap();
+    assert_eq!(user.address.city, "");
+
+    // A partial update sets the email, clears the nickname and leaves the rest.
+    let existing = User::builder()
+        .name("alice".to_owned())
+        .email("alice@example.com".to_owned())
+        .nickname(Some("al".to_owned()))
+        .quota(10)
+        .build()
+        .unwrap();
+    let mut patch = User::builder();
+    patch.email("alice@example.org".to_owned()).nickname(None);
+    let user = existing.to_builder().merge(patch).build().unwrap();
+    assert_eq!(user.name, "alice");
+    assert_eq!(user.email.as_deref(), Some("alice@example.org"));
+    assert_eq!(user.nickname, None);
+    assert_eq!(user.quota, Some(10));
+
+    let mut patch = User::builder();
+    patch.unset_quota();
+    let user = existing.to_builder().merge(patch).build().unwrap();
+    assert_eq!(user.quota, None);
+    assert_eq!(user.nickname.as_deref(), Some("al"));
+
+    let profile = Profile::builder().build().unwrap();
+    assert_eq!(profile.timezone.as_deref(), Some("UTC"));
+    let profile = Profile::builder().unset_timezone().build().unwrap();
+    assert_eq!(profile.timezone, None);
+    let err = Profile::builder()
+        .timezone("PST".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        ProfileBuilderError::Validation("unknown timezone PST".to_owned())
+    );
+
+    let builder: PointBuilder<PointBuilderUnset> = Point::builder().x(1).y(2).reset_x();
+    let point = builder.x(3).unset_y().build().unwrap();
+    assert_eq!((point.x, point.y), (3, None));
+}
@@ -0,0 +1,27 @@
+// A mergeable builder cannot have a setter named `merge`, which would collide
+// with the generated method, and typestate builders cannot be merged at all.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Strategy {
+    merge: bool,
+    #[builder(each = "merge")]
+    steps: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Renamed {
+    #[builder(name = "merge_enabled")]
+    merge: bool,
+}
+
+#[derive(Builder)]
+#[builder(typestate, merge)]
+pub struct Point {
+    x: i32,
+}
+
+fn main() {}
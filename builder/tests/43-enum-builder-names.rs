@@ -0,0 +1,13 @@
+// The builders of an enum's variants are named after the variants, so they
+// cannot all be given the same name or constructor on the enum.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(name = "MsgBuilder", constructor = "builder")]
+pub enum Msg {
+    A { x: u8 },
+    B { y: u8 },
+}
+
+fn main() {}
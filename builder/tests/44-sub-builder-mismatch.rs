@@ -0,0 +1,19 @@
+// The nested builder of a `sub_builder` field is assumed to be named after the
+// field's type and built with build(). When it is not, the errors point at the
+// field rather than at the derive.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(build_fn(name = "finish"))]
+pub struct Engine {
+    power: u32,
+}
+
+#[derive(Builder)]
+pub struct Car {
+    #[builder(sub_builder)]
+    engine: Engine,
+}
+
+fn main() {}
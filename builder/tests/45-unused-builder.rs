@@ -0,0 +1,49 @@
+// Setters of owned and immutable builders return the changed builder instead
+// of changing it in place, so calling one without using its result does
+// nothing. Like build(), they are #[must_use] so that this is warned about.
+//
+// Setters of the default mutable pattern change the builder in place and can
+// be called on their own.
+
+#![deny(unused_must_use)]
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Immutable {
+    port: u16,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Owned {
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Typed {
+    port: u16,
+}
+
+#[derive(Builder)]
+pub struct Mutable {
+    port: u16,
+}
+
+fn main() {
+    let builder = Immutable::builder();
+    builder.port(1);
+
+    Owned::builder().arg("build".to_owned());
+    Owned::builder().unset_timeout();
+
+    Typed::builder().port(1);
+
+    let mut builder = Mutable::builder();
+    builder.port(1);
+    builder.build();
+}
@@ -0,0 +1,18 @@
+// Variants without named fields get no builder, so builder attributes on them
+// or on their fields would have no effect and are rejected.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub enum Message {
+    Request {
+        id: u64,
+    },
+    #[builder(pattern = "bogus")]
+    Ping(u8),
+    Pong(#[builder(default)] u8),
+    #[builder(name = "CloseBuilder")]
+    Close,
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+// A nested builder is modified in place through the accessor of its field, and
+// its errors are turned into errors of the outer builder. Builders with the
+// owned or immutable pattern, or returning their own error type, cannot be
+// used that way and are rejected at the field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Wheel {
+    size: u8,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Seat {
+    count: u8,
+}
+
+pub struct EngineError;
+
+impl From<EngineBuilderError> for EngineError {
+    fn from(_: EngineBuilderError) -> Self {
+        EngineError
+    }
+}
+
+#[derive(Builder)]
+#[builder(build_fn(error = "EngineError"))]
+pub struct Engine {
+    power: u32,
+}
+
+#[derive(Builder)]
+pub struct Car {
+    #[builder(sub_builder)]
+    wheel: Wheel,
+    #[builder(sub_builder)]
+    seat: Seat,
+    #[builder(sub_builder)]
+    engine: Option<Engine>,
+}
+
+fn main() {}
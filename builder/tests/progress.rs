@@ -10,4 +10,43 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-missing-field-error.rs");
+    t.pass("tests/11-generics.rs");
+    t.pass("tests/12-default-field.rs");
+    t.pass("tests/13-typestate.rs");
+    t.compile_fail("tests/14-typestate-missing-field.rs");
+    t.pass("tests/15-setter-into.rs");
+    t.pass("tests/16-builder-pattern.rs");
+    t.compile_fail("tests/17-typestate-pattern.rs");
+    t.pass("tests/18-validation.rs");
+    t.pass("tests/19-each-collections.rs");
+    t.compile_fail("tests/20-malformed-field-attrs.rs");
+    t.compile_fail("tests/21-duplicate-attrs.rs");
+    t.compile_fail("tests/22-malformed-struct-attrs.rs");
+    t.pass("tests/23-enum-variants.rs");
+    t.pass("tests/24-tuple-struct.rs");
+    t.pass("tests/25-visibility.rs");
+    t.compile_fail("tests/26-private-setter.rs");
+    t.pass("tests/27-custom-names.rs");
+    t.pass("tests/28-builder-derives.rs");
+    t.pass("tests/29-to-builder.rs");
+    t.pass("tests/30-sub-builder.rs");
+    t.pass("tests/31-try-setter.rs");
+    t.pass("tests/32-no-std.rs");
+    t.pass("tests/33-option-detection.rs");
+    t.pass("tests/34-docs.rs");
+    t.pass("tests/35-skip-fields.rs");
+    t.compile_fail("tests/36-skipped-setter.rs");
+    t.pass("tests/37-merge.rs");
+    t.pass("tests/39-all-missing-fields.rs");
+    t.pass("tests/40-each-extend.rs");
+    t.pass("tests/41-reset-unset.rs");
+    t.compile_fail("tests/42-merge-conflicts.rs");
+    t.compile_fail("tests/43-enum-builder-names.rs");
+    t.compile_fail("tests/44-sub-builder-mismatch.rs");
+    t.compile_fail("tests/45-unused-builder.rs");
+    t.compile_fail("tests/46-unbuilt-variant-attrs.rs");
+    t.compile_fail("tests/47-unsupported-sub-builders.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/38-deserialize.rs");
 }